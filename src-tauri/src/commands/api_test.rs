@@ -0,0 +1,564 @@
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, StatusCode, Url};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+use super::catalog::load_providers;
+use super::config::{read_openclaw_config, OpenClawConfig};
+use super::logging::{redact, send_request};
+use super::providers::{adapter_for, resolve_endpoint};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    InvalidKey,
+    ModelNotFound,
+    RateLimited,
+    InsufficientBalance,
+    RegionBlocked,
+    EndpointUnreachable,
+    /// The API answered with an error we cannot attribute to one cause.
+    Unknown,
+}
+
+/// Phase timings in milliseconds. DNS, TCP and TLS are measured on a direct
+/// probe connection; `first_byte_ms` covers the real request up to its
+/// response headers. Phases that did not run (plain http, an earlier failure)
+/// are `None`.
+#[derive(Debug, Default, Serialize)]
+pub struct ConnectionTimings {
+    pub dns_ms: Option<u64>,
+    pub tcp_connect_ms: Option<u64>,
+    pub tls_handshake_ms: Option<u64>,
+    pub first_byte_ms: Option<u64>,
+    pub total_ms: u64,
+}
+
+/// Whatever the response headers reveal about the remaining allowance.
+#[derive(Debug, Default, Serialize)]
+pub struct RateLimitInfo {
+    pub requests_limit: Option<u64>,
+    pub requests_remaining: Option<u64>,
+    pub tokens_limit: Option<u64>,
+    pub tokens_remaining: Option<u64>,
+    /// Raw reset value as the provider sent it (seconds, duration or timestamp).
+    pub reset: Option<String>,
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiTestResult {
+    pub success: bool,
+    pub status: Option<u16>,
+    pub failure: Option<FailureKind>,
+    pub message: String,
+    pub timings: ConnectionTimings,
+    pub rate_limit: Option<RateLimitInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HopTestResult {
+    /// 0 is the primary model, then the fallbacks in order.
+    pub index: usize,
+    pub provider: String,
+    pub model: String,
+    pub result: ApiTestResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChainTestResult {
+    pub hops: Vec<HopTestResult>,
+    /// The hop that would serve requests right now.
+    pub serving_hop: Option<usize>,
+}
+
+fn ms(since: Instant) -> u64 {
+    since.elapsed().as_millis() as u64
+}
+
+/// Resolves, connects and (for https) handshakes once, timing each phase.
+async fn probe(url: &Url, timings: &mut ConnectionTimings) -> Result<(), String> {
+    let host = url.host_str().ok_or("Endpoint has no host")?.to_string();
+    let port = url.port_or_known_default().ok_or("Endpoint has no port")?;
+
+    let started = Instant::now();
+    let addr = tokio::time::timeout(PROBE_TIMEOUT, tokio::net::lookup_host((host.as_str(), port)))
+        .await
+        .map_err(|_| format!("DNS lookup for {} timed out", host))?
+        .map_err(|e| format!("DNS lookup for {} failed: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("{} did not resolve to any address", host))?;
+    timings.dns_ms = Some(ms(started));
+
+    let started = Instant::now();
+    let stream = tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(addr))
+        .await
+        .map_err(|_| format!("Connecting to {} timed out", addr))?
+        .map_err(|e| format!("Cannot connect to {}: {}", addr, e))?;
+    timings.tcp_connect_ms = Some(ms(started));
+
+    if url.scheme() == "https" {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = rustls::ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("TLS setup failed: {}", e))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let server_name = rustls::pki_types::ServerName::try_from(host.clone())
+            .map_err(|e| format!("Invalid TLS server name {}: {}", host, e))?;
+
+        let started = Instant::now();
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(config));
+        tokio::time::timeout(PROBE_TIMEOUT, connector.connect(server_name, stream))
+            .await
+            .map_err(|_| format!("TLS handshake with {} timed out", host))?
+            .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))?;
+        timings.tls_handshake_ms = Some(ms(started));
+    }
+    Ok(())
+}
+
+fn header_u64(headers: &HeaderMap, names: &[&str]) -> Option<u64> {
+    names.iter().find_map(|name| {
+        headers
+            .get(*name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+    })
+}
+
+fn header_str(headers: &HeaderMap, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| {
+        headers
+            .get(*name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+    })
+}
+
+/// Reads the OpenAI, Anthropic and IETF draft rate-limit headers.
+fn rate_limit(headers: &HeaderMap) -> Option<RateLimitInfo> {
+    let info = RateLimitInfo {
+        requests_limit: header_u64(
+            headers,
+            &["x-ratelimit-limit-requests", "anthropic-ratelimit-requests-limit", "x-ratelimit-limit", "ratelimit-limit"],
+        ),
+        requests_remaining: header_u64(
+            headers,
+            &[
+                "x-ratelimit-remaining-requests",
+                "anthropic-ratelimit-requests-remaining",
+                "x-ratelimit-remaining",
+                "ratelimit-remaining",
+            ],
+        ),
+        tokens_limit: header_u64(headers, &["x-ratelimit-limit-tokens", "anthropic-ratelimit-tokens-limit"]),
+        tokens_remaining: header_u64(
+            headers,
+            &["x-ratelimit-remaining-tokens", "anthropic-ratelimit-tokens-remaining"],
+        ),
+        reset: header_str(
+            headers,
+            &[
+                "x-ratelimit-reset-requests",
+                "anthropic-ratelimit-requests-reset",
+                "x-ratelimit-reset",
+                "ratelimit-reset",
+            ],
+        ),
+        retry_after_secs: header_u64(headers, &["retry-after"]),
+    };
+    let empty = info.requests_limit.is_none()
+        && info.requests_remaining.is_none()
+        && info.tokens_limit.is_none()
+        && info.tokens_remaining.is_none()
+        && info.reset.is_none()
+        && info.retry_after_secs.is_none();
+    (!empty).then_some(info)
+}
+
+/// Maps an error response to its most likely cause. Unambiguous status codes
+/// win; otherwise the message decides, because providers disagree on codes
+/// (Gemini reports a bad key as 400, Alibaba an unpaid account as 400).
+fn classify(status: StatusCode, message: &str) -> FailureKind {
+    let text = message.to_lowercase();
+    let mentions = |words: &[&str]| words.iter().any(|w| text.contains(w));
+    let region = mentions(&["location is not supported", "unsupported_country", "not available in your region"]);
+    let balance = mentions(&["insufficient", "balance", "quota", "billing", "credit", "arrearage", "余额"]);
+    let bad_key = mentions(&["api key", "api_key", "apikey", "authentication", "unauthorized", "x-api-key"]);
+    let no_model = text.contains("model") && mentions(&["not found", "does not exist", "not exist", "no such"]);
+
+    match status {
+        StatusCode::UNAUTHORIZED => FailureKind::InvalidKey,
+        StatusCode::PAYMENT_REQUIRED => FailureKind::InsufficientBalance,
+        StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS => FailureKind::RegionBlocked,
+        // OpenAI answers an exhausted quota with 429 as well
+        StatusCode::TOO_MANY_REQUESTS if balance => FailureKind::InsufficientBalance,
+        StatusCode::TOO_MANY_REQUESTS => FailureKind::RateLimited,
+        _ if region => FailureKind::RegionBlocked,
+        _ if balance => FailureKind::InsufficientBalance,
+        _ if bad_key => FailureKind::InvalidKey,
+        _ if no_model => FailureKind::ModelNotFound,
+        _ if mentions(&["rate limit", "too many requests"]) => FailureKind::RateLimited,
+        StatusCode::FORBIDDEN => FailureKind::InvalidKey,
+        // Nothing about a model: the path itself is wrong
+        StatusCode::NOT_FOUND => FailureKind::EndpointUnreachable,
+        _ => FailureKind::Unknown,
+    }
+}
+
+async fn run_test(request: RequestBuilder, parse_error: impl Fn(&str) -> Option<String>) -> ApiTestResult {
+    let started = Instant::now();
+    let mut timings = ConnectionTimings::default();
+
+    let (client, request) = request.build_split();
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => {
+            return ApiTestResult {
+                success: false,
+                status: None,
+                failure: Some(FailureKind::EndpointUnreachable),
+                message: format!("Invalid request: {}", e),
+                timings,
+                rate_limit: None,
+            }
+        }
+    };
+
+    // Diagnostics only: the probe bypasses any proxy, so the real request
+    // decides whether the endpoint is reachable
+    let probe_error = probe(request.url(), &mut timings).await.err();
+    if let Some(e) = &probe_error {
+        log::debug!(error:% = e; "direct connection probe failed");
+    }
+
+    let sent = Instant::now();
+    let response = send_request(RequestBuilder::from_parts(client, request)).await;
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            timings.total_ms = ms(started);
+            let mut message = format!("Connection failed: {}", redact(&e.to_string()));
+            if let Some(probe_error) = probe_error {
+                message = format!("{} ({})", message, probe_error);
+            }
+            return ApiTestResult {
+                success: false,
+                status: None,
+                failure: Some(FailureKind::EndpointUnreachable),
+                message,
+                timings,
+                rate_limit: None,
+            };
+        }
+    };
+    timings.first_byte_ms = Some(ms(sent));
+
+    let status = response.status();
+    let rate_limit = rate_limit(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    timings.total_ms = ms(started);
+
+    if status.is_success() {
+        return ApiTestResult {
+            success: true,
+            status: Some(status.as_u16()),
+            failure: None,
+            message: "Connection successful".to_string(),
+            timings,
+            rate_limit,
+        };
+    }
+
+    let message = parse_error(&body).unwrap_or(body);
+    ApiTestResult {
+        success: false,
+        status: Some(status.as_u16()),
+        failure: Some(classify(status, &message)),
+        message: format!("API returned {} — {}", status, redact(&message)),
+        timings,
+        rate_limit,
+    }
+}
+
+/// Sends the provider's cheapest request and reports what happened. API
+/// failures are part of the result; `Err` is reserved for unusable input.
+#[tauri::command]
+pub async fn test_api_connection(
+    provider: String,
+    api_key: String,
+    endpoint: String,
+    model: String,
+) -> Result<ApiTestResult, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let adapter = adapter_for(&provider);
+    let endpoint = resolve_endpoint(adapter, &endpoint)?;
+    let request = adapter.test_request(&client, &endpoint, &api_key, &model);
+    let result = run_test(request, |body| adapter.parse_error(body)).await;
+
+    log::info!(
+        provider:% = provider,
+        success = result.success,
+        failure:? = result.failure,
+        total_ms = result.timings.total_ms;
+        "api connection tested"
+    );
+    Ok(result)
+}
+
+/// Tests every hop of the fallback chain in `config` (the saved config when
+/// omitted) at once. Hops without their own endpoint use the catalog's.
+#[tauri::command]
+pub async fn test_model_chain(config: Option<OpenClawConfig>) -> Result<ChainTestResult, String> {
+    let config = match config {
+        Some(config) => config,
+        None => read_openclaw_config().await?,
+    };
+    let provider = config.model_provider.clone().ok_or("No model provider is configured")?;
+    let catalog = load_providers();
+    let catalog_endpoint = |id: &str| {
+        catalog
+            .iter()
+            .find(|p| p.id == id)
+            .and_then(|p| p.endpoint.clone())
+            .unwrap_or_default()
+    };
+
+    let mut hops = vec![(
+        provider.clone(),
+        config.model_name.clone().unwrap_or_default(),
+        config.api_key.clone().unwrap_or_default(),
+        config.api_endpoint.clone().unwrap_or_else(|| catalog_endpoint(&provider)),
+    )];
+    for hop in &config.model_fallbacks {
+        let api_key = hop.api_key.clone().or_else(|| {
+            (hop.provider == provider)
+                .then(|| config.api_key.clone())
+                .flatten()
+        });
+        hops.push((
+            hop.provider.clone(),
+            hop.model.clone(),
+            api_key.unwrap_or_default(),
+            hop.api_endpoint.clone().unwrap_or_else(|| catalog_endpoint(&hop.provider)),
+        ));
+    }
+
+    let results = futures_util::future::join_all(hops.into_iter().enumerate().map(
+        |(index, (provider, model, api_key, endpoint))| async move {
+            let result = match test_api_connection(provider.clone(), api_key, endpoint, model.clone()).await {
+                Ok(result) => result,
+                Err(e) => ApiTestResult {
+                    success: false,
+                    status: None,
+                    failure: Some(FailureKind::EndpointUnreachable),
+                    message: e,
+                    timings: ConnectionTimings::default(),
+                    rate_limit: None,
+                },
+            };
+            HopTestResult {
+                index,
+                provider,
+                model,
+                result,
+            }
+        },
+    ))
+    .await;
+
+    let serving_hop = results.iter().find(|h| h.result.success).map(|h| h.index);
+    log::info!(hops = results.len(), serving_hop:? = serving_hop; "model chain tested");
+    Ok(ChainTestResult {
+        hops: results,
+        serving_hop,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{refused_url, StubResponse, StubServer, TempHome};
+
+    #[tokio::test]
+    async fn request_is_sent_when_the_probe_fails() {
+        // Only reachable through the proxy; a direct DNS lookup fails
+        let proxy = StubServer::start(|_| StubResponse::json(200, serde_json::json!({"data": []}))).await;
+        let client = reqwest::Client::builder()
+            .proxy(reqwest::Proxy::http(&proxy.url).unwrap())
+            .build()
+            .unwrap();
+
+        let result = run_test(client.get("http://api.example.invalid/v1/models"), |_| None).await;
+        assert!(result.success, "{}", result.message);
+        assert_eq!(result.status, Some(200));
+        assert_eq!(result.timings.dns_ms, None);
+        assert_eq!(proxy.requests()[0].path, "http://api.example.invalid/v1/models");
+    }
+
+    #[tokio::test]
+    async fn unreachable_endpoint_reports_the_probe_diagnosis() {
+        let url = format!("{}/v1/models", refused_url().await);
+        let result = run_test(reqwest::Client::new().get(url), |_| None).await;
+        assert!(!result.success);
+        assert_eq!(result.failure, Some(FailureKind::EndpointUnreachable));
+        assert!(result.message.contains("Cannot connect"), "{}", result.message);
+    }
+
+    #[tokio::test]
+    async fn api_errors_are_classified() {
+        let server = StubServer::start(|_| {
+            StubResponse::json(401, serde_json::json!({"error": {"message": "Incorrect API key provided"}}))
+        })
+        .await;
+        let result = run_test(reqwest::Client::new().get(format!("{}/v1/models", server.url)), |_| None).await;
+        assert_eq!(result.status, Some(401));
+        assert_eq!(result.failure, Some(FailureKind::InvalidKey));
+        assert!(result.timings.tcp_connect_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn rate_limited_responses_carry_the_allowance() {
+        let server = StubServer::start(|_| {
+            StubResponse::json(429, serde_json::json!({"error": {"message": "Rate limit reached for requests"}}))
+                .with_header("x-ratelimit-limit-requests", "500")
+                .with_header("x-ratelimit-remaining-requests", "0")
+                .with_header("x-ratelimit-limit-tokens", "30000")
+                .with_header("x-ratelimit-remaining-tokens", "29000")
+                .with_header("x-ratelimit-reset-requests", "6m0s")
+                .with_header("retry-after", "20")
+        })
+        .await;
+        let result = run_test(reqwest::Client::new().get(format!("{}/v1/models", server.url)), |_| None).await;
+        assert_eq!(result.status, Some(429));
+        assert_eq!(result.failure, Some(FailureKind::RateLimited));
+        let info = result.rate_limit.unwrap();
+        assert_eq!((info.requests_limit, info.requests_remaining), (Some(500), Some(0)));
+        assert_eq!((info.tokens_limit, info.tokens_remaining), (Some(30000), Some(29000)));
+        assert_eq!(info.reset.as_deref(), Some("6m0s"));
+        assert_eq!(info.retry_after_secs, Some(20));
+    }
+
+    #[test]
+    fn rate_limit_reads_anthropic_and_draft_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("anthropic-ratelimit-requests-limit", "50".parse().unwrap());
+        headers.insert("anthropic-ratelimit-requests-remaining", "49".parse().unwrap());
+        headers.insert("anthropic-ratelimit-requests-reset", "2026-10-19T12:00:00Z".parse().unwrap());
+        let info = rate_limit(&headers).unwrap();
+        assert_eq!((info.requests_limit, info.requests_remaining), (Some(50), Some(49)));
+        assert_eq!(info.reset.as_deref(), Some("2026-10-19T12:00:00Z"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-remaining", "3".parse().unwrap());
+        headers.insert("retry-after", "Wed, 21 Oct 2026 07:28:00 GMT".parse().unwrap());
+        let info = rate_limit(&headers).unwrap();
+        assert_eq!(info.requests_remaining, Some(3));
+        assert_eq!(info.retry_after_secs, None);
+
+        assert!(rate_limit(&HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn failures_are_attributed_by_status_and_message() {
+        let cases = [
+            (429, "Rate limit reached for gpt-4o", FailureKind::RateLimited),
+            (429, "You exceeded your current quota, please check your plan and billing details", FailureKind::InsufficientBalance),
+            (403, "Quota exceeded for this project", FailureKind::InsufficientBalance),
+            (400, "Arrearage: account is in arrears", FailureKind::InsufficientBalance),
+            (404, "The model `gpt-5-turbo` does not exist", FailureKind::ModelNotFound),
+            (400, "model not found: qwen-ultra", FailureKind::ModelNotFound),
+            (404, "404 page not found", FailureKind::EndpointUnreachable),
+            (400, "API key not valid. Please pass a valid API key.", FailureKind::InvalidKey),
+            (403, "Forbidden", FailureKind::InvalidKey),
+            (400, "User location is not supported for the API use.", FailureKind::RegionBlocked),
+            (500, "Internal server error", FailureKind::Unknown),
+            (502, "Bad gateway", FailureKind::Unknown),
+            (503, "Too many requests, please slow down", FailureKind::RateLimited),
+        ];
+        for (status, message, expected) in cases {
+            let status = StatusCode::from_u16(status).unwrap();
+            assert_eq!(classify(status, message), expected, "{} {}", status, message);
+        }
+    }
+
+    fn chain(hops: serde_json::Value, primary_endpoint: &str) -> OpenClawConfig {
+        serde_json::from_value(serde_json::json!({
+            "model_provider": "openai",
+            "model_name": "gpt-4o",
+            "api_key": "sk-primary",
+            "api_endpoint": primary_endpoint,
+            "model_fallbacks": hops
+        }))
+        .unwrap()
+    }
+
+    fn bearer(server: &StubServer) -> Vec<String> {
+        server
+            .requests()
+            .iter()
+            .filter_map(|r| r.header("authorization").map(str::to_string))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn chain_reports_the_first_healthy_hop_and_inherits_the_primary_key() {
+        let _home = TempHome::new();
+        let down = StubServer::start(|_| {
+            StubResponse::json(503, serde_json::json!({"error": {"message": "overloaded"}}))
+        })
+        .await;
+        let healthy = StubServer::start(|_| StubResponse::json(200, serde_json::json!({"data": []}))).await;
+        let config = chain(
+            serde_json::json!([
+                {"provider": "openai", "model": "gpt-4o-mini", "api_endpoint": format!("{}/v1", healthy.url)},
+                {"provider": "deepseek", "model": "deepseek-chat", "api_endpoint": format!("{}/v1", refused_url().await)}
+            ]),
+            &format!("{}/v1", down.url),
+        );
+
+        let result = test_model_chain(Some(config)).await.unwrap();
+        let success: Vec<bool> = result.hops.iter().map(|h| h.result.success).collect();
+        assert_eq!(success, [false, true, false]);
+        assert_eq!(result.serving_hop, Some(1));
+        assert_eq!(result.hops[2].result.failure, Some(FailureKind::EndpointUnreachable));
+        // Same provider as the primary and no key of its own
+        assert_eq!(bearer(&healthy), ["Bearer sk-primary"]);
+    }
+
+    #[tokio::test]
+    async fn hops_of_the_same_model_keep_their_own_keys_through_the_store() {
+        use crate::commands::config::write_openclaw_config;
+
+        let _home = TempHome::new();
+        let down = StubServer::start(|_| StubResponse::json(401, serde_json::json!({}))).await;
+        let first = StubServer::start(|_| StubResponse::json(200, serde_json::json!({"data": []}))).await;
+        let second = StubServer::start(|_| StubResponse::json(200, serde_json::json!({"data": []}))).await;
+        let config = chain(
+            serde_json::json!([
+                {"provider": "openai", "model": "gpt-4o", "api_key": "sk-hop-one", "api_endpoint": format!("{}/v1", first.url)},
+                {"provider": "openai", "model": "gpt-4o", "api_key": "sk-hop-two", "api_endpoint": format!("{}/v1", second.url)}
+            ]),
+            &format!("{}/v1", down.url),
+        );
+        write_openclaw_config(config, None).await.unwrap();
+
+        // Reads the saved config, so the keys come back out of the secret store
+        let result = test_model_chain(None).await.unwrap();
+        assert_eq!(result.serving_hop, Some(1));
+        assert_eq!(bearer(&first), ["Bearer sk-hop-one"]);
+        assert_eq!(bearer(&second), ["Bearer sk-hop-two"]);
+    }
+}
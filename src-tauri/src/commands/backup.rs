@@ -0,0 +1,301 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config_store::{diff_json, get_config_path, get_openclaw_dir, write_file_atomic, ConfigChange, ConfigWrite};
+use super::secrets::{delete_secret, missing_secrets, secret_names};
+
+const BACKUP_DIR: &str = "backups";
+const BACKUP_PREFIX: &str = "openclaw-";
+const MAX_BACKUPS: usize = 20;
+
+#[derive(Debug, Serialize)]
+pub struct ConfigBackup {
+    pub id: String,
+    pub created_at: String,
+    pub size: u64,
+}
+
+fn backup_dir() -> Result<PathBuf, String> {
+    Ok(get_openclaw_dir()?.join(BACKUP_DIR))
+}
+
+/// Backup ids are bare file names; anything else could escape the directory.
+fn backup_path(id: &str) -> Result<PathBuf, String> {
+    let valid = id.starts_with(BACKUP_PREFIX)
+        && id.ends_with(".json")
+        && !id.contains(['/', '\\'])
+        && !id.contains("..");
+    if !valid {
+        return Err(format!("Invalid backup id: {}", id));
+    }
+    let path = backup_dir()?.join(id);
+    if !path.exists() {
+        return Err(format!("Backup not found: {}", id));
+    }
+    Ok(path)
+}
+
+fn list_backup_files() -> Result<Vec<PathBuf>, String> {
+    let dir = backup_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with(BACKUP_PREFIX) && n.ends_with(".json"))
+                .unwrap_or(false)
+        })
+        .collect();
+    // Timestamped names sort chronologically; newest first
+    files.sort();
+    files.reverse();
+    Ok(files)
+}
+
+/// Copies the current config into the backup directory under a timestamped
+/// name. `trim_backups` drops the oldest ones once the new config is written.
+pub fn create_backup(config_path: &Path) -> Result<Option<PathBuf>, String> {
+    if !config_path.exists() {
+        return Ok(None);
+    }
+    let dir = backup_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f").to_string();
+    // Writes in the same millisecond get a suffix that still sorts after the first
+    let backup = std::iter::once(format!("{}{}.json", BACKUP_PREFIX, stamp))
+        .chain((1..100).map(|n| format!("{}{}_{:02}.json", BACKUP_PREFIX, stamp, n)))
+        .map(|name| dir.join(name))
+        .find(|path| !path.exists())
+        .ok_or("Failed to back up config: too many backups at once")?;
+    fs::copy(config_path, &backup).map_err(|e| format!("Failed to back up config: {}", e))?;
+    Ok(Some(backup))
+}
+
+/// Removes the backups beyond `MAX_BACKUPS`, along with the secrets that
+/// only they still referenced. `current` is the config now on disk.
+pub fn trim_backups(current: &str) -> Result<(), String> {
+    let files = list_backup_files()?;
+    if files.len() <= MAX_BACKUPS {
+        return Ok(());
+    }
+    let (kept, expired) = files.split_at(MAX_BACKUPS);
+    let orphaned = secret_names_in(expired);
+    for old in expired {
+        let _ = fs::remove_file(old);
+    }
+
+    // Profiles keep secrets under their own names, so only the config and
+    // the remaining backups can still point at these
+    let Ok(current) = serde_json::from_str::<serde_json::Value>(current) else {
+        return Ok(());
+    };
+    let mut keep = secret_names_in(kept);
+    keep.extend(secret_names(&current));
+    for name in orphaned.difference(&keep) {
+        delete_secret(name)?;
+    }
+    Ok(())
+}
+
+/// Secrets referenced by any backup. They stay stored so that restoring a
+/// backup brings back the keys it was taken with.
+pub fn backup_secret_names() -> Result<BTreeSet<String>, String> {
+    Ok(secret_names_in(&list_backup_files()?))
+}
+
+fn secret_names_in(files: &[PathBuf]) -> BTreeSet<String> {
+    files
+        .iter()
+        .filter_map(|path| read_json(path).ok())
+        .flat_map(|doc| secret_names(&doc))
+        .collect()
+}
+
+/// Replaces secret values in every backup with the references that now
+/// stand for them, wherever in the document they appear. Backups that do
+/// not contain any of them are left untouched.
+pub fn scrub_backups(replacements: &[(String, String)]) -> Result<(), String> {
+    if replacements.is_empty() {
+        return Ok(());
+    }
+    for path in list_backup_files()? {
+        let Ok(mut doc) = read_json(&path) else {
+            continue;
+        };
+        if replace_strings(&mut doc, replacements) {
+            let json = serde_json::to_string_pretty(&doc)
+                .map_err(|e| format!("Failed to serialize backup: {}", e))?;
+            write_file_atomic(&path, &json)?;
+        }
+    }
+    Ok(())
+}
+
+fn replace_strings(value: &mut serde_json::Value, replacements: &[(String, String)]) -> bool {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => match replacements.iter().find(|(from, _)| from == s) {
+            Some((_, to)) => {
+                *s = to.clone();
+                true
+            }
+            None => false,
+        },
+        Value::Array(items) => items
+            .iter_mut()
+            .fold(false, |changed, item| replace_strings(item, replacements) || changed),
+        Value::Object(map) => map
+            .values_mut()
+            .fold(false, |changed, item| replace_strings(item, replacements) || changed),
+        _ => false,
+    }
+}
+
+fn read_json(path: &Path) -> Result<serde_json::Value, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+#[tauri::command]
+pub async fn list_config_backups() -> Result<Vec<ConfigBackup>, String> {
+    let mut backups = Vec::new();
+    for path in list_backup_files()? {
+        let meta = fs::metadata(&path).map_err(|e| format!("Failed to read backup: {}", e))?;
+        let created_at = meta
+            .modified()
+            .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339())
+            .unwrap_or_default();
+        backups.push(ConfigBackup {
+            id: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            created_at,
+            size: meta.len(),
+        });
+    }
+    Ok(backups)
+}
+
+/// Changes that restoring the backup would apply to the current config.
+#[tauri::command]
+pub async fn diff_config_backup(id: String) -> Result<Vec<ConfigChange>, String> {
+    let backup = read_json(&backup_path(&id)?)?;
+    let config_path = get_config_path()?;
+    let current = if config_path.exists() {
+        read_json(&config_path)?
+    } else {
+        serde_json::Value::Object(Default::default())
+    };
+    Ok(diff_json(&current, &backup))
+}
+
+/// Makes the backup the current config. Secret names change with their
+/// value and backups keep theirs stored, so a backup taken before a key
+/// rotation restores the key it was taken with, not the current one.
+#[tauri::command]
+pub async fn restore_config_backup(id: String) -> Result<(), String> {
+    let source = backup_path(&id)?;
+    let content = fs::read_to_string(&source).map_err(|e| format!("Failed to read backup: {}", e))?;
+    let doc: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Backup is not valid JSON: {}", e))?;
+
+    // Backups written before secrets were kept for them can point at ones
+    // that are gone; restoring those would silently blank them
+    let missing = missing_secrets(&doc)?;
+    if !missing.is_empty() {
+        log::warn!(backup:% = id, missing:? = missing; "config restore refused: secrets missing");
+        return Err(format!(
+            "Backup references secrets that are no longer stored: {}",
+            missing.join(", ")
+        ));
+    }
+
+    // The config being replaced becomes a backup itself, so a restore can be undone
+    ConfigWrite::begin(None)?.commit(&content)?;
+    log::info!(backup:% = id; "config restored from backup");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::{read_openclaw_config, write_openclaw_config, OpenClawConfig};
+    use crate::commands::secrets::load_secret;
+    use crate::commands::test_support::TempHome;
+
+    #[test]
+    fn backups_in_the_same_millisecond_are_kept() {
+        let home = TempHome::new();
+        let config = home.path().join("openclaw.json");
+        let backups: Vec<PathBuf> = (0..5)
+            .map(|i| {
+                fs::write(&config, format!("{{\"n\": {}}}", i)).unwrap();
+                create_backup(&config).unwrap().unwrap()
+            })
+            .collect();
+
+        let mut newest_first = backups.clone();
+        newest_first.reverse();
+        assert_eq!(list_backup_files().unwrap(), newest_first);
+    }
+
+    #[tokio::test]
+    async fn restore_refuses_backups_with_missing_secrets() {
+        let home = TempHome::new();
+        let config = home.path().join("openclaw.json");
+        fs::write(&config, r#"{"api_key": "secret:deleted-test-key"}"#).unwrap();
+        let backup = create_backup(&config).unwrap().unwrap();
+        fs::write(&config, "{}").unwrap();
+
+        let id = backup.file_name().unwrap().to_string_lossy().to_string();
+        let err = restore_config_backup(id).await.unwrap_err();
+        assert!(err.contains("deleted-test-key"), "{}", err);
+        assert_eq!(fs::read_to_string(&config).unwrap(), "{}");
+    }
+
+    fn with_key(key: &str) -> OpenClawConfig {
+        OpenClawConfig {
+            api_key: Some(key.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn newest_backup_id() -> String {
+        let newest = list_backup_files().unwrap().remove(0);
+        newest.file_name().unwrap().to_string_lossy().to_string()
+    }
+
+    #[tokio::test]
+    async fn restoring_a_backup_from_before_a_rotation_brings_back_the_old_key() {
+        let _home = TempHome::new();
+        write_openclaw_config(with_key("sk-before-rotation"), None).await.unwrap();
+        write_openclaw_config(with_key("sk-after-rotation"), None).await.unwrap();
+
+        restore_config_backup(newest_backup_id()).await.unwrap();
+        let restored = read_openclaw_config().await.unwrap();
+        assert_eq!(restored.api_key.as_deref(), Some("sk-before-rotation"));
+    }
+
+    #[tokio::test]
+    async fn trimmed_backups_release_their_secrets() {
+        let home = TempHome::new();
+        write_openclaw_config(with_key("sk-key-0"), None).await.unwrap();
+        let first = read_json(&home.path().join("openclaw.json")).unwrap();
+        let name = secret_names(&first).into_iter().next().unwrap();
+
+        write_openclaw_config(with_key("sk-key-1"), None).await.unwrap();
+        assert_eq!(load_secret(&name).unwrap().as_deref(), Some("sk-key-0"));
+
+        for i in 2..=MAX_BACKUPS + 1 {
+            write_openclaw_config(with_key(&format!("sk-key-{}", i)), None).await.unwrap();
+        }
+        assert_eq!(list_backup_files().unwrap().len(), MAX_BACKUPS);
+        assert_eq!(load_secret(&name).unwrap(), None);
+    }
+}
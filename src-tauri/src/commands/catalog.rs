@@ -0,0 +1,435 @@
+//! The provider catalog. A manifest is compiled into the installer; a newer
+//! signed manifest can be downloaded into `~/.openclaw/catalog`, and the
+//! user's own providers from `custom_providers.json` are merged on top.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::config_store::{get_openclaw_dir, write_file_atomic};
+use super::logging::send_request;
+use super::models::{ApiStyle, AuthStyle, ModelInfo, ModelProvider};
+
+const BUNDLED_MANIFEST: &str = include_str!("../../resources/providers.json");
+const CATALOG_DIR: &str = "catalog";
+const MANIFEST_FILE: &str = "providers.json";
+const SIGNATURE_FILE: &str = "providers.json.sig";
+const CUSTOM_PROVIDERS_FILE: &str = "custom_providers.json";
+
+/// Where updated manifests are published and the key they are signed with.
+/// Both are set at build time; without them remote refresh is unavailable.
+const CATALOG_URL: Option<&str> = option_env!("OPENCLAW_CATALOG_URL");
+const CATALOG_PUBLIC_KEY: Option<&str> = option_env!("OPENCLAW_CATALOG_PUBLIC_KEY");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogManifest {
+    pub version: u32,
+    #[serde(default)]
+    pub updated: Option<String>,
+    pub providers: Vec<ProviderEntry>,
+}
+
+/// A provider as the manifest and `custom_providers.json` describe it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub api: ApiStyle,
+    #[serde(default)]
+    pub auth: AuthStyle,
+    #[serde(default)]
+    pub docs_url: Option<String>,
+    /// Defaults to the first model.
+    #[serde(default)]
+    pub default_model: Option<String>,
+    #[serde(default)]
+    pub models: Vec<ModelInfo>,
+}
+
+impl ProviderEntry {
+    fn into_provider(self, custom: bool) -> ModelProvider {
+        let default_model = self
+            .default_model
+            .or_else(|| self.models.first().map(|m| m.id.clone()))
+            .unwrap_or_default();
+        let max_tokens = self
+            .models
+            .iter()
+            .find(|m| m.id == default_model)
+            .and_then(|m| m.max_output_tokens)
+            .unwrap_or(4096);
+        ModelProvider {
+            id: self.id,
+            name: self.name,
+            models: self.models.iter().map(|m| m.id.clone()).collect(),
+            default_model,
+            supports_streaming: self.models.iter().any(|m| m.has("streaming")),
+            max_tokens,
+            endpoint: self.endpoint,
+            api: self.api,
+            auth: self.auth,
+            docs_url: self.docs_url,
+            model_details: self.models,
+            custom,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogSource {
+    Bundled,
+    Remote,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CatalogStatus {
+    pub version: u32,
+    pub updated: Option<String>,
+    pub source: CatalogSource,
+    pub provider_count: usize,
+    pub custom_provider_count: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CustomProvidersFile {
+    #[serde(default)]
+    providers: Vec<ProviderEntry>,
+}
+
+fn catalog_dir() -> Result<PathBuf, String> {
+    Ok(get_openclaw_dir()?.join(CATALOG_DIR))
+}
+
+fn custom_providers_path() -> Result<PathBuf, String> {
+    Ok(get_openclaw_dir()?.join(CUSTOM_PROVIDERS_FILE))
+}
+
+fn parse_manifest(content: &str) -> Result<CatalogManifest, String> {
+    serde_json::from_str(content).map_err(|e| format!("Invalid provider catalog: {}", e))
+}
+
+/// Checks a detached signature against `key_b64`, normally
+/// `CATALOG_PUBLIC_KEY`.
+fn verify_signature(key_b64: Option<&str>, content: &[u8], signature_b64: &str) -> Result<(), String> {
+    let key_b64 = key_b64.ok_or("This build has no catalog signing key")?;
+    let key_bytes: [u8; 32] = BASE64
+        .decode(key_b64.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Catalog signing key is malformed")?;
+    let key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| format!("Catalog signing key is invalid: {}", e))?;
+
+    let sig_bytes: [u8; 64] = BASE64
+        .decode(signature_b64.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Catalog signature is malformed")?;
+    key.verify(content, &Signature::from_bytes(&sig_bytes))
+        .map_err(|_| "Catalog signature does not match".to_string())
+}
+
+fn bundled_manifest() -> CatalogManifest {
+    parse_manifest(BUNDLED_MANIFEST).expect("bundled provider catalog is valid")
+}
+
+/// The downloaded manifest, if it is still correctly signed.
+fn cached_manifest(key_b64: Option<&str>) -> Option<CatalogManifest> {
+    let dir = catalog_dir().ok()?;
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    let signature = fs::read_to_string(dir.join(SIGNATURE_FILE)).ok()?;
+    if let Err(e) = verify_signature(key_b64, content.as_bytes(), &signature) {
+        log::warn!(error:% = e; "ignoring downloaded provider catalog");
+        return None;
+    }
+    parse_manifest(&content).ok()
+}
+
+/// The newest trustworthy manifest: the downloaded one unless the installer
+/// itself ships a newer version.
+fn active_manifest() -> (CatalogManifest, CatalogSource) {
+    let bundled = bundled_manifest();
+    match cached_manifest(CATALOG_PUBLIC_KEY) {
+        Some(remote) if remote.version >= bundled.version => (remote, CatalogSource::Remote),
+        _ => (bundled, CatalogSource::Bundled),
+    }
+}
+
+fn custom_providers() -> Vec<ProviderEntry> {
+    let Ok(path) = custom_providers_path() else {
+        return Vec::new();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    match serde_json::from_str::<CustomProvidersFile>(&content) {
+        Ok(file) => file.providers,
+        Err(e) => {
+            log::warn!(path:% = path.display(), error:% = e; "ignoring invalid custom providers file");
+            Vec::new()
+        }
+    }
+}
+
+fn write_custom_providers(providers: Vec<ProviderEntry>) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&CustomProvidersFile { providers })
+        .map_err(|e| format!("Failed to serialize custom providers: {}", e))?;
+    write_file_atomic(&custom_providers_path()?, &json)
+}
+
+/// Catalog providers with custom providers merged on top: a custom provider
+/// with a catalog id replaces that entry, others are appended.
+pub fn load_providers() -> Vec<ModelProvider> {
+    let (manifest, _) = active_manifest();
+    let mut providers: Vec<ModelProvider> = manifest
+        .providers
+        .into_iter()
+        .map(|p| p.into_provider(false))
+        .collect();
+    for custom in custom_providers() {
+        let custom = custom.into_provider(true);
+        match providers.iter_mut().find(|p| p.id == custom.id) {
+            Some(existing) => *existing = custom,
+            None => providers.push(custom),
+        }
+    }
+    providers
+}
+
+fn catalog_status() -> CatalogStatus {
+    let (manifest, source) = active_manifest();
+    CatalogStatus {
+        version: manifest.version,
+        updated: manifest.updated,
+        source,
+        provider_count: manifest.providers.len(),
+        custom_provider_count: custom_providers().len(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_catalog_status() -> Result<CatalogStatus, String> {
+    Ok(catalog_status())
+}
+
+/// A downloaded manifest is kept only when it is signed with `key_b64` and
+/// not older than `current_version`, so an old signed catalog cannot be
+/// replayed.
+fn check_download(
+    key_b64: Option<&str>,
+    content: &str,
+    signature: &str,
+    current_version: u32,
+) -> Result<CatalogManifest, String> {
+    verify_signature(key_b64, content.as_bytes(), signature)?;
+    let manifest = parse_manifest(content)?;
+    if manifest.version < current_version {
+        return Err(format!(
+            "Downloaded catalog version {} is older than the current version {}",
+            manifest.version, current_version
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Downloads the manifest from `url` (or the build's default) and its
+/// detached ed25519 signature from `<url>.sig`, and keeps it when the
+/// signature checks out and it is at least as new as the current catalog.
+#[tauri::command]
+pub async fn refresh_provider_catalog(url: Option<String>) -> Result<CatalogStatus, String> {
+    let url = url
+        .filter(|u| !u.trim().is_empty())
+        .or_else(|| CATALOG_URL.map(str::to_string))
+        .ok_or("No catalog URL configured")?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let fetch = |url: String| {
+        let client = client.clone();
+        async move {
+            let resp = send_request(client.get(&url))
+                .await
+                .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+            if !resp.status().is_success() {
+                return Err(format!("Failed to download {}: {}", url, resp.status()));
+            }
+            resp.text()
+                .await
+                .map_err(|e| format!("Failed to download {}: {}", url, e))
+        }
+    };
+    let content = fetch(url.clone()).await?;
+    let signature = fetch(format!("{}.sig", url)).await?;
+
+    let (current, _) = active_manifest();
+    let manifest = check_download(CATALOG_PUBLIC_KEY, &content, &signature, current.version)?;
+
+    let dir = catalog_dir()?;
+    write_file_atomic(&dir.join(MANIFEST_FILE), &content)?;
+    write_file_atomic(&dir.join(SIGNATURE_FILE), &signature)?;
+    log::info!(version = manifest.version, providers = manifest.providers.len(); "provider catalog updated");
+    Ok(catalog_status())
+}
+
+/// Adds or replaces a user-defined provider.
+#[tauri::command]
+pub async fn save_custom_provider(provider: ProviderEntry) -> Result<(), String> {
+    if provider.id.trim().is_empty() || provider.name.trim().is_empty() {
+        return Err("Provider id and name are required".to_string());
+    }
+    if let Some(endpoint) = &provider.endpoint {
+        reqwest::Url::parse(endpoint).map_err(|e| format!("Invalid endpoint: {}", e))?;
+    }
+
+    let mut providers = custom_providers();
+    match providers.iter_mut().find(|p| p.id == provider.id) {
+        Some(existing) => *existing = provider,
+        None => providers.push(provider),
+    }
+    write_custom_providers(providers)
+}
+
+#[tauri::command]
+pub async fn delete_custom_provider(id: String) -> Result<(), String> {
+    let mut providers = custom_providers();
+    let before = providers.len();
+    providers.retain(|p| p.id != id);
+    if providers.len() == before {
+        return Err(format!("Custom provider not found: {}", id));
+    }
+    write_custom_providers(providers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempHome;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const BUNDLED_PROVIDERS: [&str; 12] = [
+        "alibaba",
+        "deepseek",
+        "zhipu",
+        "baidu",
+        "moonshot",
+        "ollama",
+        "anthropic",
+        "openai",
+        "google",
+        "openrouter",
+        "bedrock",
+        "siliconflow",
+    ];
+
+    #[test]
+    fn bundled_manifest_lists_every_provider() {
+        let manifest = bundled_manifest();
+        let ids: Vec<&str> = manifest.providers.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, BUNDLED_PROVIDERS);
+
+        for provider in &manifest.providers {
+            assert!(provider.endpoint.is_some(), "{} has no endpoint", provider.id);
+            assert!(!provider.models.is_empty(), "{} has no models", provider.id);
+            if let Some(default) = &provider.default_model {
+                assert!(
+                    provider.models.iter().any(|m| &m.id == default),
+                    "{} default model {} is not listed",
+                    provider.id,
+                    default
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bundled_manifest_declares_api_dialects() {
+        let manifest = bundled_manifest();
+        let api = |id: &str| manifest.providers.iter().find(|p| p.id == id).unwrap().api;
+        assert_eq!(api("anthropic"), ApiStyle::Anthropic);
+        assert_eq!(api("google"), ApiStyle::Gemini);
+        assert_eq!(api("bedrock"), ApiStyle::Bedrock);
+        assert_eq!(api("ollama"), ApiStyle::Ollama);
+        assert_eq!(api("deepseek"), ApiStyle::OpenAi);
+    }
+
+    #[test]
+    fn load_providers_serves_the_bundled_catalog() {
+        let _home = TempHome::new();
+        let ids: Vec<String> = load_providers().into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, BUNDLED_PROVIDERS);
+    }
+
+    /// A fixed test keypair: the base64 public key and a signing function.
+    fn test_key() -> (String, impl Fn(&str) -> String) {
+        let signing = SigningKey::from_bytes(&[7u8; 32]);
+        let public = BASE64.encode(signing.verifying_key().to_bytes());
+        (public, move |content: &str| BASE64.encode(signing.sign(content.as_bytes()).to_bytes()))
+    }
+
+    fn manifest_json(version: u32) -> String {
+        let mut manifest = bundled_manifest();
+        manifest.version = version;
+        serde_json::to_string_pretty(&manifest).unwrap()
+    }
+
+    #[test]
+    fn signed_manifest_is_accepted() {
+        let (key, sign) = test_key();
+        let content = manifest_json(4);
+        let manifest = check_download(Some(&key), &content, &sign(&content), 3).unwrap();
+        assert_eq!(manifest.version, 4);
+        // Same version again, e.g. a re-download
+        check_download(Some(&key), &content, &sign(&content), 4).unwrap();
+    }
+
+    #[test]
+    fn tampered_or_unsigned_manifest_is_rejected() {
+        let (key, sign) = test_key();
+        let content = manifest_json(4);
+        let signature = sign(&content);
+        let tampered = content.replace("api.deepseek.com", "deepseek.example.com");
+        assert_ne!(tampered, content);
+
+        let err = check_download(Some(&key), &tampered, &signature, 3).unwrap_err();
+        assert_eq!(err, "Catalog signature does not match");
+        let other = BASE64.encode(SigningKey::from_bytes(&[8u8; 32]).verifying_key().to_bytes());
+        let err = check_download(Some(&other), &content, &signature, 3).unwrap_err();
+        assert_eq!(err, "Catalog signature does not match");
+        let err = check_download(Some(&key), &content, "not base64!", 3).unwrap_err();
+        assert_eq!(err, "Catalog signature is malformed");
+        let err = check_download(None, &content, &signature, 3).unwrap_err();
+        assert_eq!(err, "This build has no catalog signing key");
+    }
+
+    #[test]
+    fn older_signed_manifest_is_rejected() {
+        let (key, sign) = test_key();
+        let content = manifest_json(2);
+        let err = check_download(Some(&key), &content, &sign(&content), 3).unwrap_err();
+        assert_eq!(err, "Downloaded catalog version 2 is older than the current version 3");
+    }
+
+    #[test]
+    fn cached_manifest_is_rechecked_on_load() {
+        let home = TempHome::new();
+        let (key, sign) = test_key();
+        let content = manifest_json(4);
+        let dir = home.path().join(CATALOG_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), &content).unwrap();
+        fs::write(dir.join(SIGNATURE_FILE), sign(&content)).unwrap();
+        assert_eq!(cached_manifest(Some(&key)).unwrap().version, 4);
+
+        fs::write(dir.join(MANIFEST_FILE), content.replace("\"version\": 4", "\"version\": 5")).unwrap();
+        assert!(cached_manifest(Some(&key)).is_none());
+    }
+}
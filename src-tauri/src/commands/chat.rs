@@ -0,0 +1,422 @@
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use super::catalog::load_providers;
+use super::config::{read_openclaw_config, OpenClawConfig};
+use super::logging::{redact, send_request};
+use super::models::{check_parameters, parameter_limits, ModelParameters};
+use super::providers::{adapter_for, resolve_endpoint, ProviderAdapter};
+
+pub const CHAT_TOKEN_EVENT: &str = "chat-token";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Upper bound for a whole preview, including slow reasoning models.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatToken {
+    pub request_id: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChatPreviewResult {
+    pub request_id: String,
+    pub provider: String,
+    pub model: String,
+    /// Everything streamed, also when cancelled part way.
+    pub text: String,
+    pub finish_reason: Option<String>,
+    /// `None` when the provider reported no counts.
+    pub usage: Option<TokenUsage>,
+    pub cancelled: bool,
+    pub first_token_ms: Option<u64>,
+    pub total_ms: u64,
+}
+
+fn cancellations() -> &'static Mutex<HashMap<String, Arc<Notify>>> {
+    static CANCELLATIONS: OnceLock<Mutex<HashMap<String, Arc<Notify>>>> = OnceLock::new();
+    CANCELLATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Keeps a preview cancellable for as long as it runs.
+struct Registration {
+    request_id: String,
+    notify: Arc<Notify>,
+}
+
+impl Registration {
+    /// Refuses an id that is still in use; cancelling it would otherwise
+    /// reach only one of the two previews.
+    fn new(request_id: &str) -> Result<Self, String> {
+        let mut cancellations = cancellations().lock().unwrap_or_else(|e| e.into_inner());
+        if cancellations.contains_key(request_id) {
+            return Err(format!("A chat preview with id {} is already running", request_id));
+        }
+        let notify = Arc::new(Notify::new());
+        cancellations.insert(request_id.to_string(), notify.clone());
+        Ok(Self {
+            request_id: request_id.to_string(),
+            notify,
+        })
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut cancellations = cancellations().lock().unwrap_or_else(|e| e.into_inner());
+        // Only this preview's own entry
+        if cancellations
+            .get(&self.request_id)
+            .is_some_and(|n| Arc::ptr_eq(n, &self.notify))
+        {
+            cancellations.remove(&self.request_id);
+        }
+    }
+}
+
+/// Splits a server-sent event stream into events. Bytes, not text: a
+/// multi-byte character can straddle two chunks.
+#[derive(Default)]
+struct EventBuffer(Vec<u8>);
+
+impl EventBuffer {
+    /// Adds a chunk and returns the events it completes.
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.0.extend(chunk.iter().filter(|&&b| b != b'\r'));
+        let mut events = Vec::new();
+        while let Some(end) = self.0.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.0.drain(..end + 2).collect();
+            events.push(String::from_utf8_lossy(&event).to_string());
+        }
+        events
+    }
+
+    /// What is left when the stream ends; a last event may end without its
+    /// blank line.
+    fn rest(&mut self) -> String {
+        String::from_utf8_lossy(&std::mem::take(&mut self.0)).to_string()
+    }
+}
+
+#[derive(Default)]
+struct StreamState {
+    text: String,
+    prompt_tokens: Option<u64>,
+    completion_tokens: Option<u64>,
+    finish_reason: Option<String>,
+    first_token_ms: Option<u64>,
+    done: bool,
+}
+
+/// Applies one server-sent event. Only `data:` lines matter; providers that
+/// name their events repeat the name inside the data.
+fn apply_event(
+    event: &str,
+    adapter: &dyn ProviderAdapter,
+    state: &mut StreamState,
+    started: Instant,
+    mut on_text: impl FnMut(&str),
+) -> Result<(), String> {
+    let data: Vec<&str> = event
+        .lines()
+        .filter_map(|l| l.strip_prefix("data:"))
+        .map(|d| d.strip_prefix(' ').unwrap_or(d))
+        .collect();
+    if data.is_empty() {
+        return Ok(());
+    }
+    let data = data.join("\n");
+    if data.trim() == "[DONE]" {
+        state.done = true;
+        return Ok(());
+    }
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&data) else {
+        return Ok(());
+    };
+
+    let delta = adapter.parse_stream_event(&value);
+    if let Some(error) = delta.error {
+        return Err(format!("Model returned an error: {}", redact(&error)));
+    }
+    if let Some(text) = delta.text.filter(|t| !t.is_empty()) {
+        state.first_token_ms.get_or_insert(started.elapsed().as_millis() as u64);
+        state.text.push_str(&text);
+        on_text(&text);
+    }
+    state.prompt_tokens = delta.prompt_tokens.or(state.prompt_tokens);
+    state.completion_tokens = delta.completion_tokens.or(state.completion_tokens);
+    state.finish_reason = delta.finish_reason.or(state.finish_reason.take());
+    Ok(())
+}
+
+/// Sends `prompt` to the configured model (or the one in `config`, for
+/// settings not saved yet) and streams the answer as `chat-token` events
+/// tagged with `request_id`. `cancel_chat_preview` stops it early.
+#[tauri::command]
+pub async fn chat_preview(
+    app: AppHandle,
+    request_id: String,
+    prompt: String,
+    params: Option<ModelParameters>,
+    config: Option<OpenClawConfig>,
+) -> Result<ChatPreviewResult, String> {
+    if prompt.trim().is_empty() {
+        return Err("Prompt is empty".to_string());
+    }
+    let config = match config {
+        Some(config) => config,
+        None => read_openclaw_config().await?,
+    };
+    let provider = config.model_provider.clone().ok_or("No model provider is configured")?;
+    let catalog = load_providers();
+    let entry = catalog.iter().find(|p| p.id == provider);
+    let model = config
+        .model_name
+        .clone()
+        .or_else(|| entry.map(|p| p.default_model.clone()))
+        .ok_or("No model is configured")?;
+
+    let params = params
+        .or_else(|| config.model_parameters.clone())
+        .unwrap_or_default();
+    let violations = check_parameters(&params, &parameter_limits(Some(&provider), Some(&model)));
+    if !violations.is_empty() {
+        let messages: Vec<String> = violations.into_iter().map(|v| v.message).collect();
+        return Err(format!("Invalid parameters: {}", messages.join("; ")));
+    }
+
+    let adapter = adapter_for(&provider);
+    let endpoint = config
+        .api_endpoint
+        .clone()
+        .or_else(|| entry.and_then(|p| p.endpoint.clone()))
+        .unwrap_or_default();
+    let endpoint = resolve_endpoint(adapter, &endpoint)?;
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(RESPONSE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let request = adapter
+        .chat_request(
+            &client,
+            &endpoint,
+            config.api_key.as_deref().unwrap_or_default(),
+            &model,
+            &prompt,
+            &params,
+        )
+        .ok_or_else(|| format!("Chat preview is not available for {}", provider))?;
+
+    let registration = Registration::new(&request_id)?;
+    let cancel = registration.notify.clone();
+    let started = Instant::now();
+    let mut state = StreamState::default();
+    let mut cancelled = false;
+
+    let response = tokio::select! {
+        _ = cancel.notified() => None,
+        response = send_request(request) => Some(response),
+    };
+    if let Some(response) = response {
+        let response = response.map_err(|e| format!("Connection failed: {}", redact(&e.to_string())))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let message = adapter.parse_error(&body).unwrap_or(body);
+            return Err(format!("API returned {} — {}", status, redact(&message)));
+        }
+
+        let mut stream = response.bytes_stream();
+        let mut buffer = EventBuffer::default();
+        // Straight to the frontend: the conversation stays out of the log
+        let mut on_text = |text: &str| {
+            let _ = app.emit(
+                CHAT_TOKEN_EVENT,
+                ChatToken {
+                    request_id: request_id.clone(),
+                    text: text.to_string(),
+                },
+            );
+        };
+        while !state.done {
+            let chunk = tokio::select! {
+                _ = cancel.notified() => {
+                    cancelled = true;
+                    break;
+                }
+                chunk = stream.next() => chunk,
+            };
+            let Some(chunk) = chunk else {
+                apply_event(&buffer.rest(), adapter, &mut state, started, &mut on_text)?;
+                break;
+            };
+            let chunk = chunk.map_err(|e| format!("Stream interrupted: {}", e))?;
+            for event in buffer.push(&chunk) {
+                apply_event(&event, adapter, &mut state, started, &mut on_text)?;
+            }
+        }
+    } else {
+        cancelled = true;
+    }
+
+    let usage = (state.prompt_tokens.is_some() || state.completion_tokens.is_some()).then_some(TokenUsage {
+        prompt_tokens: state.prompt_tokens,
+        completion_tokens: state.completion_tokens,
+    });
+    let total_ms = started.elapsed().as_millis() as u64;
+    log::info!(
+        provider:% = provider,
+        model:% = model,
+        cancelled,
+        prompt_tokens:? = state.prompt_tokens,
+        completion_tokens:? = state.completion_tokens,
+        total_ms;
+        "chat preview finished"
+    );
+    Ok(ChatPreviewResult {
+        request_id,
+        provider,
+        model,
+        text: state.text,
+        finish_reason: state.finish_reason,
+        usage,
+        cancelled,
+        first_token_ms: state.first_token_ms,
+        total_ms,
+    })
+}
+
+/// Stops a running `chat_preview`. Returns false when it already finished.
+#[tauri::command]
+pub async fn cancel_chat_preview(request_id: String) -> Result<bool, String> {
+    let notify = cancellations()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&request_id)
+        .cloned();
+    match notify {
+        Some(notify) => {
+            notify.notify_one();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::models::AuthStyle;
+    use crate::commands::providers::{Anthropic, OpenAiCompatible};
+
+    const OPENAI: OpenAiCompatible = OpenAiCompatible { auth: AuthStyle::Bearer };
+
+    fn apply_all(adapter: &dyn ProviderAdapter, events: &[String], state: &mut StreamState) -> Result<Vec<String>, String> {
+        let mut streamed = Vec::new();
+        for event in events {
+            apply_event(event, adapter, state, Instant::now(), |t| streamed.push(t.to_string()))?;
+        }
+        Ok(streamed)
+    }
+
+    #[test]
+    fn events_split_across_chunks() {
+        let stream = "data: {\"choices\":[{\"delta\":{\"content\":\"Héllo\"}}]}\r\n\r\n\
+                      data: {\"choices\":[{\"delta\":{\"content\":\" world\"},\"finish_reason\":\"stop\"}]}\n\n\
+                      data: [DONE]\n\n";
+        let bytes = stream.as_bytes();
+        // Cuts inside the JSON, inside the two-byte é and between the two newlines
+        let e_acute = stream.find('é').unwrap() + 1;
+        let blank = stream.find("\n\n").unwrap() + 1;
+        let mut cuts = vec![0, 7, e_acute, blank, bytes.len() - 3, bytes.len()];
+        cuts.sort();
+
+        let mut buffer = EventBuffer::default();
+        let mut events = Vec::new();
+        for pair in cuts.windows(2) {
+            events.extend(buffer.push(&bytes[pair[0]..pair[1]]));
+        }
+        assert_eq!(events.len(), 3, "{:?}", events);
+        assert_eq!(buffer.rest(), "");
+
+        let mut state = StreamState::default();
+        let streamed = apply_all(&OPENAI, &events, &mut state).unwrap();
+        assert_eq!(streamed, ["Héllo", " world"]);
+        assert_eq!(state.text, "Héllo world");
+        assert_eq!(state.finish_reason.as_deref(), Some("stop"));
+        assert!(state.done);
+        assert!(state.first_token_ms.is_some());
+    }
+
+    #[test]
+    fn last_event_without_blank_line_is_kept() {
+        let mut buffer = EventBuffer::default();
+        assert!(buffer.push(b"data: [DONE]").is_empty());
+        let mut state = StreamState::default();
+        apply_event(&buffer.rest(), &OPENAI, &mut state, Instant::now(), |_| {}).unwrap();
+        assert!(state.done);
+    }
+
+    #[test]
+    fn error_delta_fails_the_preview() {
+        let mut state = StreamState::default();
+        let events = [
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n".to_string(),
+            "event: error\ndata: {\"type\":\"error\",\"error\":{\"message\":\"Overloaded\"}}\n\n".to_string(),
+        ];
+        let err = apply_all(&Anthropic, &events[1..], &mut state).unwrap_err();
+        assert!(err.contains("Overloaded"), "{}", err);
+
+        let openai_error = "data: {\"error\":{\"message\":\"quota exceeded\"}}\n\n".to_string();
+        let err = apply_all(&OPENAI, &[events[0].clone(), openai_error], &mut state).unwrap_err();
+        assert!(err.contains("quota exceeded"), "{}", err);
+        assert_eq!(state.text, "Hi");
+    }
+
+    #[test]
+    fn usage_only_final_event_keeps_the_answer() {
+        let mut state = StreamState::default();
+        let events = [
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"},\"finish_reason\":\"stop\"}]}\n\n".to_string(),
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":1}}\n\n".to_string(),
+            "data: [DONE]\n\n".to_string(),
+        ];
+        let streamed = apply_all(&OPENAI, &events, &mut state).unwrap();
+        assert_eq!(streamed, ["Hi"]);
+        assert_eq!(state.prompt_tokens, Some(5));
+        assert_eq!(state.completion_tokens, Some(1));
+        assert_eq!(state.finish_reason.as_deref(), Some("stop"));
+        assert!(state.done);
+    }
+
+    #[test]
+    fn request_ids_are_not_shared() {
+        let first = Registration::new("preview-test").unwrap();
+        assert!(Registration::new("preview-test").is_err());
+        drop(first);
+
+        let second = Registration::new("preview-test").unwrap();
+        // An entry that is not its own survives the drop
+        let other = Arc::new(Notify::new());
+        cancellations()
+            .lock()
+            .unwrap()
+            .insert("preview-test".to_string(), other.clone());
+        drop(second);
+        let remaining = cancellations().lock().unwrap().remove("preview-test");
+        assert!(remaining.is_some_and(|n| Arc::ptr_eq(&n, &other)));
+    }
+}
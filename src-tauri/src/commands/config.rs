@@ -1,16 +1,279 @@
 use serde::{Deserialize, Serialize};
-use std::fs;
 use std::net::{TcpStream, ToSocketAddrs};
 use std::time::Duration;
 
+use super::config_store::{
+    config_revision, load_stored_config, render_config, revision_of, ConfigWrite,
+};
+use super::migrations::CURRENT_SCHEMA_VERSION;
+use super::models::ModelParameters;
+use super::secrets::{externalize_secrets, prune_secrets, resolve_secrets};
+
+/// Gateway intents the Discord bot subscribes to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscordIntent {
+    Guilds,
+    GuildMembers,
+    GuildMessages,
+    DirectMessages,
+    /// Privileged; must also be enabled in the developer portal.
+    MessageContent,
+}
+
+fn default_discord_intents() -> Vec<DiscordIntent> {
+    vec![
+        DiscordIntent::Guilds,
+        DiscordIntent::GuildMessages,
+        DiscordIntent::DirectMessages,
+        DiscordIntent::MessageContent,
+    ]
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlackMode {
+    /// Events over a websocket; needs `app_token`.
+    #[default]
+    Socket,
+    /// Events posted to the gateway; needs `signing_secret`.
+    Http,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhatsAppPairing {
+    /// Scan a QR code from the phone on first start.
+    #[default]
+    QrCode,
+    /// Enter a code sent to `phone_number` instead.
+    PhoneCode,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    /// Chats the bot answers in; empty allows all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_chat_ids: Vec<i64>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PlatformEntry {
+pub struct DiscordConfig {
+    pub bot_token: String,
+    #[serde(default = "default_discord_intents")]
+    pub intents: Vec<DiscordIntent>,
+    /// Guild ids the bot may join; empty allows all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guild_allowlist: Vec<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            bot_token: String::new(),
+            intents: default_discord_intents(),
+            guild_allowlist: Vec::new(),
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlackConfig {
+    /// `xoxb-` token used for the Web API.
+    pub bot_token: String,
+    #[serde(default)]
+    pub mode: SlackMode,
+    /// `xapp-` token for socket mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WhatsAppConfig {
+    #[serde(default)]
+    pub pairing: WhatsAppPairing,
+    /// In international format; required for `phone_code` pairing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    /// Senders the bot answers; empty allows all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_from: Vec<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A platform the installer has no typed settings for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenericPlatform {
     pub platform: String,
+    #[serde(default)]
     pub token: String,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One messaging platform, tagged by `platform`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "platform", rename_all = "lowercase")]
+pub enum PlatformEntry {
+    Telegram(TelegramConfig),
+    Discord(DiscordConfig),
+    Slack(SlackConfig),
+    WhatsApp(WhatsAppConfig),
+    #[serde(untagged)]
+    Other(GenericPlatform),
+}
+
+impl PlatformEntry {
+    pub fn platform(&self) -> &str {
+        match self {
+            Self::Telegram(_) => "telegram",
+            Self::Discord(_) => "discord",
+            Self::Slack(_) => "slack",
+            Self::WhatsApp(_) => "whatsapp",
+            Self::Other(p) => &p.platform,
+        }
+    }
+
+    /// Field holding the platform's main credential, if it has one.
+    pub fn token_field(platform: &str) -> Option<&'static str> {
+        match platform.to_ascii_lowercase().as_str() {
+            "telegram" | "discord" | "slack" => Some("bot_token"),
+            "whatsapp" => None,
+            _ => Some("token"),
+        }
+    }
+
+    /// The token the platform's API is called with.
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            Self::Telegram(c) => Some(&c.bot_token),
+            Self::Discord(c) => Some(&c.bot_token),
+            Self::Slack(c) => Some(&c.bot_token),
+            Self::WhatsApp(_) => None,
+            Self::Other(p) => Some(&p.token),
+        }
+    }
+
+    /// Every credential field that is set, by field name.
+    pub fn secrets(&self) -> Vec<(&'static str, &String)> {
+        match self {
+            Self::Telegram(c) => vec![("bot_token", &c.bot_token)],
+            Self::Discord(c) => vec![("bot_token", &c.bot_token)],
+            Self::Slack(c) => {
+                let mut secrets = vec![("bot_token", &c.bot_token)];
+                secrets.extend(c.app_token.as_ref().map(|t| ("app_token", t)));
+                secrets.extend(c.signing_secret.as_ref().map(|s| ("signing_secret", s)));
+                secrets
+            }
+            Self::WhatsApp(_) => Vec::new(),
+            Self::Other(p) => vec![("token", &p.token)],
+        }
+    }
+
+    pub fn secrets_mut(&mut self) -> Vec<(&'static str, &mut String)> {
+        match self {
+            Self::Telegram(c) => vec![("bot_token", &mut c.bot_token)],
+            Self::Discord(c) => vec![("bot_token", &mut c.bot_token)],
+            Self::Slack(c) => {
+                let mut secrets = vec![("bot_token", &mut c.bot_token)];
+                secrets.extend(c.app_token.as_mut().map(|t| ("app_token", t)));
+                secrets.extend(c.signing_secret.as_mut().map(|s| ("signing_secret", s)));
+                secrets
+            }
+            Self::WhatsApp(_) => Vec::new(),
+            Self::Other(p) => vec![("token", &mut p.token)],
+        }
+    }
+}
+
+/// Accepts the typed shape and the older `{platform, token}` one, where the
+/// single token becomes the platform's main credential. WhatsApp pairs
+/// instead of using a token, so an old one is dropped.
+impl<'de> Deserialize<'de> for PlatformEntry {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let mut map = serde_json::Map::deserialize(deserializer)?;
+        let platform = map
+            .get("platform")
+            .and_then(|p| p.as_str())
+            .ok_or_else(|| D::Error::missing_field("platform"))?
+            .to_string();
+        let kind = platform.to_ascii_lowercase();
+        if kind == "whatsapp" && map.remove("token").is_some() {
+            log::warn!("dropped legacy whatsapp token; WhatsApp pairs instead");
+        }
+        if let Some(field) = PlatformEntry::token_field(&kind).filter(|f| *f != "token") {
+            if !map.contains_key(field) {
+                if let Some(token) = map.remove("token") {
+                    map.insert(field.to_string(), token);
+                }
+            }
+        }
+
+        let typed = |mut map: serde_json::Map<String, serde_json::Value>| {
+            map.remove("platform");
+            serde_json::Value::Object(map)
+        };
+        let entry = match kind.as_str() {
+            "telegram" => serde_json::from_value(typed(map)).map(Self::Telegram),
+            "discord" => serde_json::from_value(typed(map)).map(Self::Discord),
+            "slack" => serde_json::from_value(typed(map)).map(Self::Slack),
+            "whatsapp" => serde_json::from_value(typed(map)).map(Self::WhatsApp),
+            _ => serde_json::from_value(serde_json::Value::Object(map)).map(Self::Other),
+        };
+        entry.map_err(|e| D::Error::custom(format!("{} platform: {}", platform, e)))
+    }
+}
+
+/// What makes a hop in the fallback chain hand a request to the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FallbackTrigger {
+    RateLimited,
+    Timeout { after_ms: u64 },
+    /// Any other failed request.
+    Error,
+}
+
+fn default_fallback_triggers() -> Vec<FallbackTrigger> {
+    vec![
+        FallbackTrigger::RateLimited,
+        FallbackTrigger::Timeout { after_ms: 30_000 },
+        FallbackTrigger::Error,
+    ]
+}
+
+/// A model tried after the primary one, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelFallback {
+    pub provider: String,
+    pub model: String,
+    /// Defaults to the primary `api_key` when the provider is the same.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_endpoint: Option<String>,
+    /// Failures of the previous hop that lead to this one.
+    #[serde(default = "default_fallback_triggers")]
+    pub triggers: Vec<FallbackTrigger>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenClawConfig {
+    #[serde(default)]
+    pub schema_version: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_provider: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -19,56 +282,84 @@ pub struct OpenClawConfig {
     pub api_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub model_fallbacks: Vec<ModelFallback>,
+    #[serde(default = "default_gateway_port")]
     pub gateway_port: u16,
     #[serde(default)]
     pub platforms: Vec<PlatformEntry>,
+    /// Name of the preset `model_parameters` was taken from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_preset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_parameters: Option<ModelParameters>,
+    /// Keys the installer does not model, written by OpenClaw or the user.
+    /// Kept so that a read/write round trip never drops them.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+pub fn default_gateway_port() -> u16 {
+    18789
 }
 
 impl Default for OpenClawConfig {
     fn default() -> Self {
         Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
             model_provider: None,
             model_name: None,
             api_key: None,
             api_endpoint: None,
-            gateway_port: 18789,
+            model_fallbacks: Vec::new(),
+            gateway_port: default_gateway_port(),
             platforms: Vec::new(),
+            model_preset: None,
+            model_parameters: None,
+            extra: serde_json::Map::new(),
         }
     }
 }
 
-fn get_openclaw_dir() -> Result<std::path::PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
-    Ok(home.join(".openclaw"))
-}
-
+/// Writes the config and returns the new revision. When `expected_revision`
+/// is given and the file on disk no longer matches it, someone else changed
+/// the config since the caller read it and the write is refused.
 #[tauri::command]
-pub async fn write_openclaw_config(config: OpenClawConfig) -> Result<(), String> {
-    let dir = get_openclaw_dir()?;
-    fs::create_dir_all(&dir)
-        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+pub async fn write_openclaw_config(
+    mut config: OpenClawConfig,
+    expected_revision: Option<String>,
+) -> Result<String, String> {
+    let mut write = ConfigWrite::begin(expected_revision.as_deref())?;
+
+    config.schema_version = CURRENT_SCHEMA_VERSION;
+    externalize_secrets(&mut config)?;
 
-    let config_path = dir.join("openclaw.json");
-    let json = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let previous = load_stored_config().ok().flatten();
+    let json = render_config(&config)?;
+    write.commit(&json)?;
 
-    fs::write(&config_path, json)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
+    // Only once the new file no longer references them
+    if let Some(previous) = previous {
+        if let Err(e) = prune_secrets(&previous, &config) {
+            log::warn!(error:% = e; "failed to delete unused secrets");
+        }
+    }
+    Ok(revision_of(&json))
+}
 
-    Ok(())
+/// Revision of the config on disk, to pass back to `write_openclaw_config`.
+#[tauri::command]
+pub async fn get_config_revision() -> Result<Option<String>, String> {
+    config_revision()
 }
 
 #[tauri::command]
 pub async fn read_openclaw_config() -> Result<OpenClawConfig, String> {
-    let dir = get_openclaw_dir()?;
-    let config_path = dir.join("openclaw.json");
-    if !config_path.exists() {
+    let Some(mut config) = load_stored_config()? else {
         return Ok(OpenClawConfig::default());
-    }
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))
+    };
+    resolve_secrets(&mut config)?;
+    Ok(config)
 }
 
 #[tauri::command]
@@ -91,54 +382,30 @@ pub async fn detect_npm_registry() -> Result<String, String> {
     }
 }
 
-#[tauri::command]
-pub async fn test_api_connection(
-    provider: String,
-    api_key: String,
-    endpoint: String,
-    model: String,
-) -> Result<String, String> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(15))
-        .build()
-        .map_err(|e| format!("HTTP client error: {}", e))?;
-
-    // Build request based on provider type
-    let (url, body) = match provider.as_str() {
-        "ollama" => {
-            let url = format!("{}/api/tags", endpoint.trim_end_matches('/'));
-            return match client.get(&url).send().await {
-                Ok(resp) if resp.status().is_success() => Ok("Ollama connected".to_string()),
-                Ok(resp) => Err(format!("Ollama returned status {}", resp.status())),
-                Err(e) => Err(format!("Cannot reach Ollama: {}", e)),
-            };
-        }
-        _ => {
-            // OpenAI-compatible API (works for most providers)
-            let url = format!("{}/chat/completions", endpoint.trim_end_matches('/'));
-            let body = serde_json::json!({
-                "model": model,
-                "messages": [{"role": "user", "content": "hi"}],
-                "max_tokens": 5
-            });
-            (url, body)
-        }
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let resp = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| format!("Connection failed: {}", e))?;
-
-    if resp.status().is_success() {
-        Ok("Connection successful".to_string())
-    } else {
-        let status = resp.status();
-        let text = resp.text().await.unwrap_or_default();
-        Err(format!("API returned {} — {}", status, text))
+    #[test]
+    fn legacy_tokens_move_to_typed_fields() {
+        let entry: PlatformEntry =
+            serde_json::from_value(serde_json::json!({"platform": "Telegram", "token": "123:abc"})).unwrap();
+        assert_eq!(entry.token(), Some("123:abc"));
+        assert_eq!(serde_json::to_value(&entry).unwrap()["bot_token"], "123:abc");
+    }
+
+    #[test]
+    fn legacy_whatsapp_token_is_dropped() {
+        let entry: PlatformEntry = serde_json::from_value(serde_json::json!({
+            "platform": "whatsapp",
+            "token": "secret:platform-whatsapp-token",
+            "pairing": "qr_code"
+        }))
+        .unwrap();
+        let PlatformEntry::WhatsApp(config) = &entry else {
+            panic!("not a WhatsApp entry: {:?}", entry);
+        };
+        assert!(config.extra.is_empty(), "{:?}", config.extra);
+        assert!(serde_json::to_value(&entry).unwrap().get("token").is_none());
     }
 }
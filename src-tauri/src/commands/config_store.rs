@@ -0,0 +1,434 @@
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use super::backup;
+use super::config::OpenClawConfig;
+use super::logging::{is_secret_key, redact_json, REDACTED};
+use super::migrations::migrate_document;
+
+pub const CONFIG_FILE: &str = "openclaw.json";
+
+/// Overrides the OpenClaw state directory, like OpenClaw itself honors it.
+pub const OPENCLAW_HOME_ENV: &str = "OPENCLAW_HOME";
+
+/// The single place that decides where OpenClaw's state lives. Every module
+/// that touches `openclaw.json` goes through here.
+///
+/// `OPENCLAW_HOME` wins when set (a leading `~` is expanded). Otherwise it is
+/// `~/.openclaw` on every OS: `$HOME` on Linux and macOS, `%USERPROFILE%` on
+/// Windows, which is where the OpenClaw CLI and the Docker volume look.
+pub fn get_openclaw_dir() -> Result<PathBuf, String> {
+    if let Ok(custom) = std::env::var(OPENCLAW_HOME_ENV) {
+        let custom = custom.trim();
+        if !custom.is_empty() {
+            return Ok(expand_home(custom));
+        }
+    }
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    Ok(home.join(".openclaw"))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => match dirs::home_dir() {
+            Some(home) => home.join(rest.trim_start_matches(['/', '\\'])),
+            None => PathBuf::from(path),
+        },
+        None => PathBuf::from(path),
+    }
+}
+
+pub fn get_config_path() -> Result<PathBuf, String> {
+    Ok(get_openclaw_dir()?.join(CONFIG_FILE))
+}
+
+/// Writes `contents` to a temp file in the same directory, fsyncs it and
+/// renames it over `path`, so readers never see a half-written file.
+pub fn write_file_atomic(path: &Path, contents: &str) -> Result<(), String> {
+    let dir = path.parent().ok_or("Invalid config path")?;
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    tmp.write_all(contents.as_bytes())
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+    tmp.as_file()
+        .sync_all()
+        .map_err(|e| format!("Failed to sync temp file: {}", e))?;
+    tmp.persist(path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e.error))?;
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Ok(d) = fs::File::open(dir) {
+        let _ = d.sync_all();
+    }
+    Ok(())
+}
+
+/// Overlays `new` onto `old`, keeping the key order of `old` for keys present
+/// in both and appending keys that only `new` has. Keys missing from `new` are
+/// dropped, so `new` decides what the document contains.
+pub fn merge_preserving_order(old: &serde_json::Value, new: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(mut new_map)) => {
+            let mut merged = serde_json::Map::new();
+            for (k, old_value) in old_map {
+                if let Some(new_value) = new_map.remove(k) {
+                    merged.insert(k.clone(), merge_preserving_order(old_value, new_value));
+                }
+            }
+            merged.extend(new_map);
+            Value::Object(merged)
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => Value::Array(
+            new_items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| match old_items.get(i) {
+                    Some(old_item) => merge_preserving_order(old_item, item),
+                    None => item,
+                })
+                .collect(),
+        ),
+        (_, new) => new,
+    }
+}
+
+/// Serializes `config` into the on-disk document, keeping the layout of the
+/// existing file where possible.
+pub fn render_config(config: &OpenClawConfig) -> Result<String, String> {
+    let new = serde_json::to_value(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let existing = get_config_path()
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok());
+    let document = match existing {
+        Some(old) => merge_preserving_order(&old, new),
+        None => new,
+    };
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize config: {}", e))
+}
+
+/// Content hash identifying one version of the config file.
+pub fn revision_of(contents: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(contents.as_bytes()))
+}
+
+/// Revision of the config file on disk, or `None` when it does not exist.
+pub fn config_revision() -> Result<Option<String>, String> {
+    let path = get_config_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read config: {}", e))?;
+    Ok(Some(revision_of(&content)))
+}
+
+/// Serializes every writer of the config file, so the revision check and the
+/// write happen as one step.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+const STALE_REVISION: &str =
+    "Config was changed by another program since it was loaded. Reload it and try again.";
+
+/// Exclusive right to replace the config file. Every writer takes one before
+/// reading the file it is about to change and commits through it.
+pub struct ConfigWrite {
+    _guard: MutexGuard<'static, ()>,
+    revision: Option<String>,
+}
+
+impl ConfigWrite {
+    /// Takes the write lock. When `expected_revision` is given and the file on
+    /// disk no longer matches it, someone else changed the config since the
+    /// caller read it and the write is refused.
+    pub fn begin(expected_revision: Option<&str>) -> Result<Self, String> {
+        let guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let revision = config_revision()?;
+        if let Some(expected) = expected_revision {
+            if revision.as_deref() != Some(expected) {
+                log::warn!(expected:% = expected, current:? = revision; "config write rejected: modified externally");
+                return Err(STALE_REVISION.to_string());
+            }
+        }
+        Ok(Self { _guard: guard, revision })
+    }
+
+    /// Revision of the file when the lock was taken, `None` if it did not exist.
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    /// Backs up the current file and atomically replaces it, unless another
+    /// program changed it since `begin`. Returns the backup path. The lock is
+    /// held until the `ConfigWrite` is dropped.
+    pub fn commit(&mut self, contents: &str) -> Result<Option<PathBuf>, String> {
+        let current = config_revision()?;
+        if current != self.revision {
+            log::warn!(expected:? = self.revision, current:? = current; "config write rejected: modified externally");
+            return Err(STALE_REVISION.to_string());
+        }
+        let path = get_config_path()?;
+        let backup = backup::create_backup(&path)?;
+        write_file_atomic(&path, contents)?;
+        self.revision = Some(revision_of(contents));
+        if let Err(e) = backup::trim_backups(contents) {
+            log::warn!(error:% = e; "failed to trim config backups");
+        }
+        Ok(backup)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    /// JSON pointer of the changed value, e.g. `/platforms/0/bot_token`.
+    pub path: String,
+    pub kind: String, // "added" | "removed" | "changed"
+    pub old: Option<serde_json::Value>,
+    pub new: Option<serde_json::Value>,
+}
+
+/// Lists leaf-level differences between two JSON documents.
+pub fn diff_json(old: &serde_json::Value, new: &serde_json::Value) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    diff_value("", old, new, &mut changes);
+    changes
+}
+
+/// Hides secret values in a change before it is shown or sent anywhere.
+pub fn mask_change(mut change: ConfigChange) -> ConfigChange {
+    let key = change.path.rsplit('/').next().unwrap_or_default().to_string();
+    for value in [change.old.as_mut(), change.new.as_mut()].into_iter().flatten() {
+        match value {
+            serde_json::Value::String(s) if is_secret_key(&key) && !s.is_empty() => *s = REDACTED.to_string(),
+            v => redact_json(v),
+        }
+    }
+    change
+}
+
+fn diff_value(
+    path: &str,
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    changes: &mut Vec<ConfigChange>,
+) {
+    use serde_json::Value;
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (k, av) in a {
+                let child = format!("{}/{}", path, escape_pointer(k));
+                match b.get(k) {
+                    Some(bv) => diff_value(&child, av, bv, changes),
+                    None => changes.push(ConfigChange {
+                        path: child,
+                        kind: "removed".to_string(),
+                        old: Some(av.clone()),
+                        new: None,
+                    }),
+                }
+            }
+            for (k, bv) in b {
+                if !a.contains_key(k) {
+                    changes.push(ConfigChange {
+                        path: format!("{}/{}", path, escape_pointer(k)),
+                        kind: "added".to_string(),
+                        old: None,
+                        new: Some(bv.clone()),
+                    });
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child = format!("{}/{}", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(av), Some(bv)) => diff_value(&child, av, bv, changes),
+                    (Some(av), None) => changes.push(ConfigChange {
+                        path: child,
+                        kind: "removed".to_string(),
+                        old: Some(av.clone()),
+                        new: None,
+                    }),
+                    (None, Some(bv)) => changes.push(ConfigChange {
+                        path: child,
+                        kind: "added".to_string(),
+                        old: None,
+                        new: Some(bv.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if old != new => changes.push(ConfigChange {
+            path: path.to_string(),
+            kind: "changed".to_string(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+pub fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Reads and migrates the config file as a raw document, without resolving
+/// secret references. Returns `None` when the file does not exist yet.
+pub fn load_stored_document() -> Result<Option<serde_json::Value>, String> {
+    let config_path = get_config_path()?;
+    if !config_path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+    let mut doc: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    let report = migrate_document(&mut doc)?;
+    if report.changed() {
+        log::info!(from = report.from_version, to = report.to_version; "config upgraded in memory");
+    }
+    Ok(Some(doc))
+}
+
+/// Like `load_stored_document`, parsed into an `OpenClawConfig`.
+pub fn load_stored_config() -> Result<Option<OpenClawConfig>, String> {
+    let Some(doc) = load_stored_document()? else {
+        return Ok(None);
+    };
+    serde_json::from_value(doc)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse config: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::backup::{diff_config_backup, list_config_backups, restore_config_backup};
+    use crate::commands::config::{get_config_revision, read_openclaw_config, write_openclaw_config};
+    use crate::commands::effective::get_effective_config;
+    use crate::commands::migrations::{migrate_config, CURRENT_SCHEMA_VERSION};
+    use crate::commands::doctor::run_doctor;
+    use crate::commands::test_support::TempHome;
+    use crate::commands::troubleshoot::{check_config_file, fix_config_file};
+
+    fn config(model: &str) -> OpenClawConfig {
+        OpenClawConfig {
+            model_provider: Some("deepseek".to_string()),
+            model_name: Some(model.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn read_file(home: &TempHome) -> serde_json::Value {
+        let content = fs::read_to_string(home.path().join(CONFIG_FILE)).expect("config file exists");
+        serde_json::from_str(&content).unwrap()
+    }
+
+    #[tokio::test]
+    async fn every_command_uses_the_home_config() {
+        let home = TempHome::new();
+        assert_eq!(get_config_path().unwrap(), home.path().join(CONFIG_FILE));
+        assert!(check_config_file().is_err());
+
+        let revision = write_openclaw_config(config("deepseek-chat"), None).await.unwrap();
+        assert_eq!(read_file(&home)["model_name"], "deepseek-chat");
+        assert_eq!(get_config_revision().await.unwrap(), Some(revision));
+        assert_eq!(read_openclaw_config().await.unwrap().model_name.as_deref(), Some("deepseek-chat"));
+        assert_eq!(load_stored_document().unwrap().unwrap()["model_name"], "deepseek-chat");
+
+        let effective = get_effective_config(None).await.unwrap();
+        assert_eq!(effective.config.model_name.as_deref(), Some("deepseek-chat"));
+
+        check_config_file().unwrap();
+        let doctor = run_doctor().await.unwrap();
+        let check = doctor.iter().find(|c| c.id == "config").unwrap();
+        assert!(check.ok);
+        assert_eq!(check.message, home.path().join(CONFIG_FILE).to_string_lossy());
+
+        fix_config_file().await.unwrap();
+        assert_eq!(read_file(&home)["model_name"], "qwen-plus");
+    }
+
+    #[tokio::test]
+    async fn stale_revision_is_refused() {
+        let home = TempHome::new();
+        let revision = write_openclaw_config(config("deepseek-chat"), None).await.unwrap();
+
+        let path = home.path().join(CONFIG_FILE);
+        let external = fs::read_to_string(&path).unwrap().replace("deepseek-chat", "deepseek-reasoner");
+        fs::write(&path, &external).unwrap();
+
+        let err = write_openclaw_config(config("other"), Some(revision)).await.unwrap_err();
+        assert!(err.contains("changed by another program"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), external);
+    }
+
+    #[tokio::test]
+    async fn external_edit_while_writing_is_refused() {
+        let home = TempHome::new();
+        write_openclaw_config(config("deepseek-chat"), None).await.unwrap();
+
+        let mut write = ConfigWrite::begin(None).unwrap();
+        let path = home.path().join(CONFIG_FILE);
+        fs::write(&path, "{}").unwrap();
+
+        let err = write.commit(r#"{"model_name": "other"}"#).unwrap_err();
+        assert!(err.contains("changed by another program"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+    }
+
+    #[tokio::test]
+    async fn writes_are_backed_up_and_restorable() {
+        let home = TempHome::new();
+        write_openclaw_config(config("deepseek-chat"), None).await.unwrap();
+        assert!(list_config_backups().await.unwrap().is_empty());
+
+        write_openclaw_config(config("deepseek-reasoner"), None).await.unwrap();
+        let backups = list_config_backups().await.unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(home.path().join("backups").join(&backups[0].id).exists());
+
+        let changes = diff_config_backup(backups[0].id.clone()).await.unwrap();
+        assert!(changes.iter().any(|c| c.path == "/model_name"));
+
+        restore_config_backup(backups[0].id.clone()).await.unwrap();
+        assert_eq!(read_file(&home)["model_name"], "deepseek-chat");
+        assert_eq!(read_openclaw_config().await.unwrap().model_name.as_deref(), Some("deepseek-chat"));
+        // The replaced config became a backup too
+        assert_eq!(list_config_backups().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn migration_rewrites_the_home_config() {
+        let home = TempHome::new();
+        let path = home.path().join(CONFIG_FILE);
+        let legacy = r#"{"gateway_port": "18790", "platforms": [{"platform": "telegram", "token": ""}]}"#;
+        fs::write(&path, legacy).unwrap();
+
+        let report = migrate_config(Some(true)).await.unwrap();
+        assert!(report.changed());
+        assert_eq!(fs::read_to_string(&path).unwrap(), legacy);
+
+        let report = migrate_config(None).await.unwrap();
+        assert_eq!(report.to_version, CURRENT_SCHEMA_VERSION);
+        let backup = report.backup.expect("migration takes a backup");
+        assert_eq!(fs::read_to_string(backup).unwrap(), legacy);
+
+        let doc = read_file(&home);
+        assert_eq!(doc["schema_version"], CURRENT_SCHEMA_VERSION);
+        assert_eq!(doc["gateway_port"], 18790);
+        assert_eq!(doc["platforms"][0]["bot_token"], "");
+        assert_eq!(read_openclaw_config().await.unwrap().gateway_port, 18790);
+    }
+}
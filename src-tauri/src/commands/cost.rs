@@ -0,0 +1,458 @@
+//! Spending estimates from usage statistics and catalog list prices, and
+//! budgets that raise `budget-alert` when crossed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::catalog::load_providers;
+use super::config_store::{get_openclaw_dir, write_file_atomic};
+use super::logging::emit;
+use super::models::{ModelPricing, ModelProvider};
+use super::usage::{collect_usage, ModelUsageStats, UsageWindow};
+
+pub const BUDGET_ALERT_EVENT: &str = "budget-alert";
+
+const BUDGETS_FILE: &str = "budgets.json";
+/// How often the background monitor re-checks budgets.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const REPORT_PERIODS: [UsageWindow; 3] = [UsageWindow::Day, UsageWindow::Week, UsageWindow::Month];
+
+/// A spending limit over a rolling window, for one provider or all of them.
+/// Only costs in the budget's currency count against it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Budget {
+    /// `None` covers every provider.
+    #[serde(default)]
+    pub provider: Option<String>,
+    pub period: UsageWindow,
+    pub limit: f64,
+    pub currency: String,
+}
+
+impl Budget {
+    fn key(&self) -> String {
+        format!(
+            "{}|{:?}|{}|{}",
+            self.provider.as_deref().unwrap_or("*"),
+            self.period,
+            self.currency,
+            self.limit
+        )
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BudgetsFile {
+    #[serde(default)]
+    budgets: Vec<Budget>,
+    /// Keys of the budgets already alerted about and still over their limit,
+    /// so a restart does not alert again.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    alerted: BTreeSet<String>,
+}
+
+/// Serializes read-modify-write cycles of `budgets.json`; the monitor and
+/// the commands both update it.
+static BUDGETS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCost {
+    pub provider: String,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// `None` when the catalog has no price for the model.
+    pub cost: Option<f64>,
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrencyTotal {
+    pub currency: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodCost {
+    pub period: UsageWindow,
+    /// One entry per currency; prices are never converted.
+    pub totals: Vec<CurrencyTotal>,
+    pub models: Vec<ModelCost>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub budget: Budget,
+    pub spent: f64,
+    /// `spent / limit`.
+    pub used: f64,
+    pub exceeded: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CostReport {
+    pub periods: Vec<PeriodCost>,
+    pub budgets: Vec<BudgetStatus>,
+}
+
+fn budgets_path() -> Result<PathBuf, String> {
+    Ok(get_openclaw_dir()?.join(BUDGETS_FILE))
+}
+
+fn read_budgets_file() -> Result<BudgetsFile, String> {
+    let path = budgets_path()?;
+    if !path.exists() {
+        return Ok(BudgetsFile::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read budgets: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse budgets: {}", e))
+}
+
+fn write_budgets_file(file: &BudgetsFile) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(file).map_err(|e| format!("Failed to serialize budgets: {}", e))?;
+    write_file_atomic(&budgets_path()?, &json)
+}
+
+fn load_budgets() -> Result<Vec<Budget>, String> {
+    Ok(read_budgets_file()?.budgets)
+}
+
+fn pricing_for<'a>(catalog: &'a [ModelProvider], provider: &str, model: &str) -> Option<&'a ModelPricing> {
+    catalog
+        .iter()
+        .find(|p| p.id == provider)
+        .and_then(|p| p.model_details.iter().find(|m| m.id == model))
+        .and_then(|m| m.pricing.as_ref())
+}
+
+fn period_cost(period: UsageWindow, catalog: &[ModelProvider]) -> Result<PeriodCost, String> {
+    Ok(price_usage(period, collect_usage(period.since())?, catalog))
+}
+
+/// Prices `usage` at catalog list prices and totals it per currency.
+fn price_usage(period: UsageWindow, usage: Vec<ModelUsageStats>, catalog: &[ModelProvider]) -> PeriodCost {
+    let models: Vec<ModelCost> = usage
+        .into_iter()
+        .map(|u| {
+            let pricing = pricing_for(catalog, &u.provider, &u.model);
+            ModelCost {
+                cost: pricing.map(|p| {
+                    (u.prompt_tokens as f64 * p.input_per_million + u.completion_tokens as f64 * p.output_per_million)
+                        / 1_000_000.0
+                }),
+                currency: pricing.map(|p| p.currency.clone()),
+                provider: u.provider,
+                model: u.model,
+                prompt_tokens: u.prompt_tokens,
+                completion_tokens: u.completion_tokens,
+            }
+        })
+        .collect();
+
+    let mut totals: Vec<CurrencyTotal> = Vec::new();
+    for m in &models {
+        let (Some(cost), Some(currency)) = (m.cost, &m.currency) else {
+            continue;
+        };
+        match totals.iter_mut().find(|t| &t.currency == currency) {
+            Some(total) => total.amount += cost,
+            None => totals.push(CurrencyTotal {
+                currency: currency.clone(),
+                amount: cost,
+            }),
+        }
+    }
+    PeriodCost { period, totals, models }
+}
+
+fn budget_statuses(
+    budgets: Vec<Budget>,
+    periods: &mut Vec<PeriodCost>,
+    catalog: &[ModelProvider],
+) -> Result<Vec<BudgetStatus>, String> {
+    let mut statuses = Vec::new();
+    for budget in budgets {
+        // Budgets may use windows the report does not show
+        if !periods.iter().any(|p| p.period == budget.period) {
+            periods.push(period_cost(budget.period, catalog)?);
+        }
+        let period = periods.iter().find(|p| p.period == budget.period).expect("period computed above");
+        let spent: f64 = period
+            .models
+            .iter()
+            .filter(|m| budget.provider.as_ref().is_none_or(|p| p == &m.provider))
+            .filter(|m| m.currency.as_deref() == Some(budget.currency.as_str()))
+            .filter_map(|m| m.cost)
+            .sum();
+        let used = if budget.limit > 0.0 { spent / budget.limit } else { 0.0 };
+        statuses.push(BudgetStatus {
+            exceeded: spent >= budget.limit,
+            budget,
+            spent,
+            used,
+        });
+    }
+    Ok(statuses)
+}
+
+/// Budgets that went over since the last check, updating `alerted`. A
+/// budget alerts again only after dropping back under its limit.
+fn newly_exceeded<'a>(alerted: &mut BTreeSet<String>, statuses: &'a [BudgetStatus]) -> Vec<&'a BudgetStatus> {
+    let mut crossed = Vec::new();
+    for status in statuses {
+        let key = status.budget.key();
+        if !status.exceeded {
+            alerted.remove(&key);
+        } else if alerted.insert(key) {
+            crossed.push(status);
+        }
+    }
+    crossed
+}
+
+/// `newly_exceeded` against the alert state kept in `budgets.json`.
+fn record_alerts(statuses: &[BudgetStatus]) -> Result<Vec<BudgetStatus>, String> {
+    let _guard = BUDGETS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut file = read_budgets_file()?;
+    let before = file.alerted.clone();
+    let crossed: Vec<BudgetStatus> = newly_exceeded(&mut file.alerted, statuses).into_iter().cloned().collect();
+    if file.alerted != before {
+        write_budgets_file(&file)?;
+    }
+    Ok(crossed)
+}
+
+/// Emits `budget-alert` for budgets that went over since the last check.
+fn alert_crossed(app: &AppHandle, statuses: &[BudgetStatus]) -> Result<(), String> {
+    for status in record_alerts(statuses)? {
+        log::warn!(
+            provider:? = status.budget.provider,
+            period:? = status.budget.period,
+            spent = status.spent,
+            limit = status.budget.limit;
+            "budget exceeded"
+        );
+        emit(app, BUDGET_ALERT_EVENT, status);
+    }
+    Ok(())
+}
+
+fn check_budgets(app: &AppHandle) -> Result<(), String> {
+    let budgets = load_budgets()?;
+    if budgets.is_empty() {
+        return Ok(());
+    }
+    let catalog = load_providers();
+    let statuses = budget_statuses(budgets, &mut Vec::new(), &catalog)?;
+    alert_crossed(app, &statuses)
+}
+
+/// Re-checks budgets every `CHECK_INTERVAL` on a background thread, so
+/// alerts arrive without the cost page being open.
+pub fn start_budget_monitor(app: AppHandle) -> Result<(), String> {
+    std::thread::Builder::new()
+        .name("budget-monitor".to_string())
+        .spawn(move || loop {
+            if let Err(e) = check_budgets(&app) {
+                log::warn!(error:% = e; "budget check failed");
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        })
+        .map_err(|e| format!("Failed to start budget monitor: {}", e))?;
+    Ok(())
+}
+
+/// Estimated spending over the last day, week and month, per model and per
+/// currency, with the state of every budget.
+#[tauri::command]
+pub async fn get_cost_report(app: AppHandle) -> Result<CostReport, String> {
+    let catalog = load_providers();
+    let mut periods = REPORT_PERIODS
+        .iter()
+        .map(|p| period_cost(*p, &catalog))
+        .collect::<Result<Vec<_>, _>>()?;
+    let budgets = budget_statuses(load_budgets()?, &mut periods, &catalog)?;
+    if let Err(e) = alert_crossed(&app, &budgets) {
+        log::warn!(error:% = e; "budget alerts not recorded");
+    }
+    periods.retain(|p| REPORT_PERIODS.contains(&p.period));
+    Ok(CostReport { periods, budgets })
+}
+
+#[tauri::command]
+pub async fn get_budgets() -> Result<Vec<Budget>, String> {
+    load_budgets()
+}
+
+#[tauri::command]
+pub async fn save_budgets(budgets: Vec<Budget>) -> Result<(), String> {
+    for budget in &budgets {
+        if budget.limit.is_nan() || budget.limit <= 0.0 {
+            return Err("Budget limit must be greater than zero".to_string());
+        }
+        if budget.currency.trim().is_empty() {
+            return Err("Budget currency is required".to_string());
+        }
+    }
+    let budgets: Vec<Budget> = budgets
+        .into_iter()
+        .map(|b| Budget {
+            currency: b.currency.trim().to_uppercase(),
+            ..b
+        })
+        .collect();
+
+    let _guard = BUDGETS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // Unchanged budgets stay alerted; edited or removed ones start over
+    let keys: BTreeSet<String> = budgets.iter().map(Budget::key).collect();
+    let mut alerted = read_budgets_file().map(|f| f.alerted).unwrap_or_default();
+    alerted.retain(|k| keys.contains(k));
+    write_budgets_file(&BudgetsFile { budgets, alerted })?;
+    log::info!("budgets saved");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempHome;
+
+    fn usage(provider: &str, model: &str, prompt: u64, completion: u64) -> ModelUsageStats {
+        ModelUsageStats {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            total_requests: 1,
+            failed_requests: 0,
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+            avg_response_time_ms: 0,
+            p50_response_time_ms: None,
+            p95_response_time_ms: None,
+            p99_response_time_ms: None,
+            success_rate: 1.0,
+            error_rate: 0.0,
+        }
+    }
+
+    fn budget(provider: Option<&str>, limit: f64, currency: &str) -> Budget {
+        Budget {
+            provider: provider.map(str::to_string),
+            period: UsageWindow::Day,
+            limit,
+            currency: currency.to_string(),
+        }
+    }
+
+    fn status(budget: Budget, exceeded: bool) -> BudgetStatus {
+        BudgetStatus {
+            budget,
+            spent: 0.0,
+            used: 0.0,
+            exceeded,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    /// gpt-4o: $2.50 in / $10 out; gpt-4o-mini: $0.15 / $0.60;
+    /// deepseek-chat: ¥2 / ¥8 per million tokens.
+    fn day(catalog: &[ModelProvider]) -> PeriodCost {
+        price_usage(
+            UsageWindow::Day,
+            vec![
+                usage("openai", "gpt-4o", 1_000_000, 500_000),
+                usage("openai", "gpt-4o-mini", 2_000_000, 1_000_000),
+                usage("deepseek", "deepseek-chat", 500_000, 250_000),
+                usage("openrouter", "openrouter/auto", 1_000, 1_000),
+            ],
+            catalog,
+        )
+    }
+
+    #[test]
+    fn costs_are_priced_per_model_and_totalled_per_currency() {
+        let _home = TempHome::new();
+        let catalog = load_providers();
+        let day = day(&catalog);
+
+        assert!(close(day.models[0].cost.unwrap(), 7.5));
+        assert!(close(day.models[1].cost.unwrap(), 0.9));
+        assert!(close(day.models[2].cost.unwrap(), 3.0));
+        assert_eq!(day.models[2].currency.as_deref(), Some("CNY"));
+        // No list price, so no cost and no total
+        assert_eq!(day.models[3].cost, None);
+
+        let totals: Vec<(&str, f64)> = day.totals.iter().map(|t| (t.currency.as_str(), t.amount)).collect();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].0, "USD");
+        assert!(close(totals[0].1, 8.4));
+        assert_eq!(totals[1].0, "CNY");
+        assert!(close(totals[1].1, 3.0));
+    }
+
+    #[test]
+    fn budgets_count_only_their_provider_and_currency() {
+        let _home = TempHome::new();
+        let catalog = load_providers();
+        let mut periods = vec![day(&catalog)];
+        let statuses = budget_statuses(
+            vec![
+                budget(None, 10.0, "USD"),
+                budget(Some("openai"), 8.0, "USD"),
+                budget(Some("deepseek"), 5.0, "USD"),
+                budget(Some("deepseek"), 2.0, "CNY"),
+            ],
+            &mut periods,
+            &catalog,
+        )
+        .unwrap();
+
+        let spent: Vec<f64> = statuses.iter().map(|s| s.spent).collect();
+        assert!(close(spent[0], 8.4) && close(spent[1], 8.4));
+        assert!(close(spent[2], 0.0), "CNY spending does not count against a USD budget");
+        assert!(close(spent[3], 3.0));
+        let exceeded: Vec<bool> = statuses.iter().map(|s| s.exceeded).collect();
+        assert_eq!(exceeded, [false, true, false, true]);
+        assert!(close(statuses[0].used, 0.84));
+    }
+
+    #[test]
+    fn a_budget_alerts_once_until_it_drops_back_under() {
+        let mut alerted = BTreeSet::new();
+        let over = [status(budget(None, 1.0, "USD"), true)];
+        let under = [status(budget(None, 1.0, "USD"), false)];
+
+        assert_eq!(newly_exceeded(&mut alerted, &over).len(), 1);
+        assert!(newly_exceeded(&mut alerted, &over).is_empty());
+        assert!(newly_exceeded(&mut alerted, &under).is_empty());
+        assert_eq!(newly_exceeded(&mut alerted, &over).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn alert_state_survives_a_restart_and_budget_edits_rearm_it() {
+        let _home = TempHome::new();
+        let kept = budget(None, 1.0, "USD");
+        let edited = budget(Some("openai"), 2.0, "USD");
+        save_budgets(vec![kept.clone(), edited.clone()]).await.unwrap();
+
+        let over = [status(kept.clone(), true), status(edited.clone(), true)];
+        assert_eq!(record_alerts(&over).unwrap().len(), 2);
+        // Nothing in memory: the second check reads the state back from disk
+        assert!(record_alerts(&over).unwrap().is_empty());
+
+        let raised = budget(Some("openai"), 3.0, "USD");
+        save_budgets(vec![kept.clone(), raised.clone()]).await.unwrap();
+        assert_eq!(get_budgets().await.unwrap().len(), 2);
+        let crossed = record_alerts(&[status(kept, true), status(raised, true)]).unwrap();
+        assert_eq!(crossed.len(), 1);
+        assert_eq!(crossed[0].budget.limit, 3.0);
+    }
+}
@@ -2,6 +2,8 @@ use serde::Serialize;
 use std::process::Command;
 use sysinfo::{Disks, System};
 
+use super::logging::run_command;
+
 #[derive(Debug, Serialize)]
 pub struct EnvDetectionResult {
     pub os_ok: bool,
@@ -46,7 +48,7 @@ fn check_os() -> (bool, String) {
 }
 
 fn check_nodejs() -> (bool, String) {
-    match Command::new("node").arg("--version").output() {
+    match run_command(Command::new("node").arg("--version")) {
         Ok(output) if output.status.success() => {
             let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
             // Parse major version from "v22.x.x"
@@ -63,7 +65,7 @@ fn check_nodejs() -> (bool, String) {
 }
 
 fn check_docker() -> (bool, String) {
-    match Command::new("docker").arg("--version").output() {
+    match run_command(Command::new("docker").arg("--version")) {
         Ok(output) if output.status.success() => {
             let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
             (true, version)
@@ -76,9 +78,7 @@ fn check_wsl2() -> bool {
     if !cfg!(target_os = "windows") {
         return false;
     }
-    Command::new("wsl")
-        .arg("--status")
-        .output()
+    run_command(Command::new("wsl").arg("--status"))
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
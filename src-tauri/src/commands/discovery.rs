@@ -0,0 +1,192 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use super::config_store::revision_of;
+use super::logging::send_request;
+use super::models::{get_available_providers, ApiStyle};
+use super::ollama::normalize_tag;
+use super::providers::{adapter_for, resolve_endpoint};
+
+/// How long a listing is reused before the provider is asked again.
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredModel {
+    pub id: String,
+    /// Listed in the installer's catalog.
+    pub in_catalog: bool,
+    /// Returned by the provider for this key; `None` when discovery failed or
+    /// the provider has no listing endpoint.
+    pub available: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelDiscoveryResult {
+    pub provider: String,
+    pub models: Vec<DiscoveredModel>,
+    pub from_cache: bool,
+    /// Why only the catalog could be returned.
+    pub error: Option<String>,
+}
+
+struct CacheEntry {
+    fetched: Instant,
+    models: Vec<String>,
+}
+
+fn cache() -> &'static Mutex<HashMap<String, CacheEntry>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CacheEntry>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Provider, endpoint and a hash of the key, since different keys can see
+/// different models.
+fn cache_key(provider: &str, endpoint: &str, api_key: &str) -> String {
+    format!("{}|{}|{}", provider, endpoint, revision_of(api_key))
+}
+
+async fn fetch_models(provider: &str, endpoint: &str, api_key: &str) -> Result<Vec<String>, String> {
+    let adapter = adapter_for(provider);
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let request = adapter
+        .models_request(&client, endpoint, api_key)
+        .ok_or("This provider does not offer a model list")?;
+
+    let resp = send_request(request)
+        .await
+        .map_err(|e| format!("Connection failed: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.unwrap_or_default();
+    if !status.is_success() {
+        let message = adapter.parse_error(&text).unwrap_or(text);
+        return Err(format!("API returned {} — {}", status, message));
+    }
+
+    let body: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid model list: {}", e))?;
+    let mut models = adapter.parse_models(&body);
+    models.sort();
+    models.dedup();
+    Ok(models)
+}
+
+/// Lists the models `api_key` can use, merged with the catalog: catalog
+/// models come first in catalog order, followed by models only the provider
+/// knows about. Listings are cached for `CACHE_TTL` unless `refresh` is set.
+#[tauri::command]
+pub async fn discover_models(
+    provider: String,
+    endpoint: String,
+    api_key: String,
+    refresh: Option<bool>,
+) -> Result<ModelDiscoveryResult, String> {
+    let adapter = adapter_for(&provider);
+    let endpoint = resolve_endpoint(adapter, &endpoint)?;
+    let key = cache_key(&provider, &endpoint, &api_key);
+
+    let cached = if refresh.unwrap_or(false) {
+        None
+    } else {
+        let cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .get(&key)
+            .filter(|entry| entry.fetched.elapsed() < CACHE_TTL)
+            .map(|entry| entry.models.clone())
+    };
+    let from_cache = cached.is_some();
+
+    let listed = match cached {
+        Some(models) => Ok(models),
+        None => {
+            let result = fetch_models(&provider, &endpoint, &api_key).await;
+            if let Ok(models) = &result {
+                let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+                cache.insert(
+                    key,
+                    CacheEntry {
+                        fetched: Instant::now(),
+                        models: models.clone(),
+                    },
+                );
+            }
+            result
+        }
+    };
+
+    let entry = get_available_providers().await?.into_iter().find(|p| p.id == provider);
+    // Ollama lists the catalog's `llama3` as `llama3:latest`
+    let ollama = entry.as_ref().is_some_and(|p| p.api == ApiStyle::Ollama);
+    let canonical = |id: &str| if ollama { normalize_tag(id) } else { id.to_string() };
+    let catalog = entry.map(|p| p.models).unwrap_or_default();
+
+    let (listed, error) = match listed {
+        Ok(models) => (Some(models), None),
+        Err(e) => {
+            log::warn!(provider:% = provider, error:% = e; "model discovery failed");
+            (None, Some(e))
+        }
+    };
+    let listed_ids: Option<Vec<String>> = listed.as_ref().map(|l| l.iter().map(|id| canonical(id)).collect());
+
+    let mut models: Vec<DiscoveredModel> = catalog
+        .iter()
+        .map(|id| DiscoveredModel {
+            id: id.clone(),
+            in_catalog: true,
+            available: listed_ids.as_ref().map(|l| l.contains(&canonical(id))),
+        })
+        .collect();
+    let catalog_ids: Vec<String> = catalog.iter().map(|id| canonical(id)).collect();
+    for id in listed.iter().flatten() {
+        if !catalog_ids.contains(&canonical(id)) {
+            models.push(DiscoveredModel {
+                id: id.clone(),
+                in_catalog: false,
+                available: Some(true),
+            });
+        }
+    }
+
+    Ok(ModelDiscoveryResult {
+        provider,
+        models,
+        from_cache,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{StubResponse, StubServer, TempHome};
+
+    #[tokio::test]
+    async fn ollama_tags_match_untagged_catalog_models() {
+        // The catalog is read from the OpenClaw dir, so keep the real one out
+        let _home = TempHome::new();
+        let server = StubServer::start(|_| {
+            StubResponse::json(
+                200,
+                serde_json::json!({"models": [{"name": "llama3:latest"}, {"name": "mistral:7b"}]}),
+            )
+        })
+        .await;
+
+        let result = discover_models("ollama".to_string(), server.url.clone(), String::new(), Some(true))
+            .await
+            .unwrap();
+        assert_eq!(result.error, None);
+        let llama = result.models.iter().find(|m| m.id == "llama3").unwrap();
+        assert!(llama.in_catalog);
+        assert_eq!(llama.available, Some(true));
+        assert!(!result.models.iter().any(|m| m.id == "llama3:latest"));
+        let mistral = result.models.iter().find(|m| m.id == "mistral:7b").unwrap();
+        assert!(!mistral.in_catalog);
+    }
+}
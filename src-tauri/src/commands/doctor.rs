@@ -1,6 +1,9 @@
 use serde::Serialize;
 use std::process::Command;
 
+use super::config_store::get_config_path;
+use super::logging::run_command;
+
 #[derive(Debug, Serialize)]
 pub struct DoctorCheck {
     pub id: String,
@@ -14,9 +17,7 @@ pub async fn run_doctor() -> Result<Vec<DoctorCheck>, String> {
     let mut checks = Vec::new();
 
     // Check Node.js
-    let node_ok = Command::new("node")
-        .arg("--version")
-        .output()
+    let node_ok = run_command(Command::new("node").arg("--version"))
         .map(|o| o.status.success())
         .unwrap_or(false);
     checks.push(DoctorCheck {
@@ -34,9 +35,7 @@ pub async fn run_doctor() -> Result<Vec<DoctorCheck>, String> {
     } else {
         "openclaw".to_string()
     };
-    let oc_ok = Command::new(&openclaw_cmd)
-        .arg("--version")
-        .output()
+    let oc_ok = run_command(Command::new(&openclaw_cmd).arg("--version"))
         .map(|o| o.status.success())
         .unwrap_or(false);
     checks.push(DoctorCheck {
@@ -60,8 +59,7 @@ pub async fn run_doctor() -> Result<Vec<DoctorCheck>, String> {
     });
 
     // Check config file
-    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
-    let config_path = home.join(".openclaw").join("openclaw.json");
+    let config_path = get_config_path()?;
     let config_ok = config_path.exists();
     checks.push(DoctorCheck {
         id: "config".to_string(),
@@ -2,7 +2,9 @@ use futures_util::StreamExt;
 use serde::Serialize;
 use std::io::Write;
 use std::time::Instant;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
+
+use super::logging::{self, send_request};
 
 #[derive(Clone, Serialize)]
 pub struct DownloadProgress {
@@ -34,12 +36,10 @@ pub async fn download_dependency(
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
-    let response = client
-        .get(url)
-        .send()
+    let response = send_request(client.get(url))
         .await
         .map_err(|e| {
-            let _ = app.emit("download-progress", DownloadProgress {
+            logging::emit(&app, "download-progress", DownloadProgress {
                 id: dep_id.clone(),
                 downloaded: 0,
                 total: 0,
@@ -52,7 +52,7 @@ pub async fn download_dependency(
 
     if !response.status().is_success() {
         let msg = format!("HTTP error: {}", response.status());
-        let _ = app.emit("download-progress", DownloadProgress {
+        logging::emit(&app, "download-progress", DownloadProgress {
             id: dep_id.clone(),
             downloaded: 0,
             total: 0,
@@ -91,7 +91,7 @@ pub async fn download_dependency(
                 0
             };
 
-            let _ = app.emit("download-progress", DownloadProgress {
+            logging::emit(&app, "download-progress", DownloadProgress {
                 id: dep_id.clone(),
                 downloaded,
                 total,
@@ -104,7 +104,7 @@ pub async fn download_dependency(
     }
 
     // Final progress emit
-    let _ = app.emit("download-progress", DownloadProgress {
+    logging::emit(&app, "download-progress", DownloadProgress {
         id: dep_id.clone(),
         downloaded,
         total,
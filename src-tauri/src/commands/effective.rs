@@ -0,0 +1,289 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use super::config::{OpenClawConfig, PlatformEntry};
+use super::config_store::load_stored_document;
+use super::platforms::platform_schemas;
+use super::secrets::resolve_secrets;
+
+const ENV_PREFIX: &str = "OPENCLAW_";
+const PLATFORM_TOKEN_SUFFIX: &str = "_TOKEN";
+
+/// Scalar fields that can be overridden, with their environment variable.
+/// The command-line flag is the field name in kebab case, e.g. `--api-key`.
+const OVERRIDABLE_FIELDS: &[(&str, &str)] = &[
+    ("model_provider", "OPENCLAW_MODEL_PROVIDER"),
+    ("model_name", "OPENCLAW_MODEL_NAME"),
+    ("api_key", "OPENCLAW_API_KEY"),
+    ("api_endpoint", "OPENCLAW_API_ENDPOINT"),
+    ("gateway_port", "OPENCLAW_GATEWAY_PORT"),
+];
+
+/// Canonical name of a platform with a settings schema, matched ignoring
+/// case, so that e.g. `OPENCLAW_GATEWAY_TOKEN` is not read as a platform token.
+fn known_platform(name: &str) -> Option<&'static str> {
+    platform_schemas()
+        .into_iter()
+        .map(|s| s.platform)
+        .find(|p| p.eq_ignore_ascii_case(name))
+}
+
+/// Layers in increasing precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Environment,
+    CommandLine,
+    Override,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectiveConfig {
+    pub config: OpenClawConfig,
+    /// Layer each value came from, keyed by JSON pointer.
+    pub sources: BTreeMap<String, ConfigSource>,
+    /// Overrides that were ignored, e.g. a non-numeric port.
+    pub warnings: Vec<String>,
+}
+
+struct Layers {
+    doc: Map<String, Value>,
+    sources: BTreeMap<String, ConfigSource>,
+    warnings: Vec<String>,
+}
+
+impl Layers {
+    fn set(&mut self, field: &str, raw: &str, source: ConfigSource) {
+        let value = if field == "gateway_port" {
+            match raw.trim().parse::<u16>() {
+                Ok(port) if port > 0 => Value::from(port),
+                _ => {
+                    self.warnings.push(format!(
+                        "Ignored gateway_port \"{}\" from {:?}: not a valid port",
+                        raw, source
+                    ));
+                    return;
+                }
+            }
+        } else {
+            Value::String(raw.to_string())
+        };
+        self.doc.insert(field.to_string(), value);
+        self.sources.insert(format!("/{}", field), source);
+    }
+
+    fn set_platform_token(&mut self, platform: &str, token: &str, source: ConfigSource) {
+        let Some(field) = PlatformEntry::token_field(platform) else {
+            self.warnings
+                .push(format!("Ignored token for {}: it does not use one", platform));
+            return;
+        };
+        let platforms = self
+            .doc
+            .entry("platforms")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        let Value::Array(entries) = platforms else {
+            return;
+        };
+        let index = match entries
+            .iter()
+            .position(|e| {
+                e.get("platform")
+                    .and_then(|p| p.as_str())
+                    .is_some_and(|p| p.eq_ignore_ascii_case(platform))
+            })
+        {
+            Some(i) => {
+                if let Some(entry) = entries[i].as_object_mut() {
+                    // An older entry's `token` would otherwise be read instead
+                    entry.remove("token");
+                    entry.insert(field.to_string(), Value::String(token.to_string()));
+                }
+                i
+            }
+            None => {
+                entries.push(serde_json::json!({ "platform": platform, field: token }));
+                entries.len() - 1
+            }
+        };
+        self.sources
+            .insert(format!("/platforms/{}/{}", index, field), source);
+    }
+
+    fn apply_file(&mut self, file: Map<String, Value>) {
+        for (k, v) in file {
+            if let Value::Array(items) = &v {
+                if k == "platforms" {
+                    for (i, item) in items.iter().enumerate() {
+                        let field = item
+                            .get("platform")
+                            .and_then(|p| p.as_str())
+                            .and_then(PlatformEntry::token_field);
+                        if let Some(field) = field {
+                            self.sources
+                                .insert(format!("/platforms/{}/{}", i, field), ConfigSource::File);
+                        }
+                    }
+                }
+            }
+            self.sources.insert(format!("/{}", k), ConfigSource::File);
+            self.doc.insert(k, v);
+        }
+    }
+
+    fn apply_env(&mut self) {
+        for (field, var) in OVERRIDABLE_FIELDS {
+            if let Ok(value) = std::env::var(var) {
+                self.set(field, &value, ConfigSource::Environment);
+            }
+        }
+        // OPENCLAW_<PLATFORM>_TOKEN, e.g. OPENCLAW_TELEGRAM_TOKEN
+        let mut platform_vars: Vec<(String, String)> = std::env::vars()
+            .filter(|(k, _)| !OVERRIDABLE_FIELDS.iter().any(|(_, var)| var == k))
+            .filter_map(|(k, v)| {
+                let platform = k.strip_prefix(ENV_PREFIX)?.strip_suffix(PLATFORM_TOKEN_SUFFIX)?;
+                known_platform(platform).map(|p| (p.to_string(), v))
+            })
+            .collect();
+        platform_vars.sort();
+        for (platform, token) in platform_vars {
+            self.set_platform_token(&platform, &token, ConfigSource::Environment);
+        }
+    }
+
+    /// Accepts `--api-key value` and `--api-key=value` for every overridable
+    /// field, plus `--<platform>-token`.
+    fn apply_args(&mut self, args: &[String]) {
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                continue;
+            };
+            let (name, inline) = match flag.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (flag, None),
+            };
+            let field = name.replace('-', "_");
+            let is_field = OVERRIDABLE_FIELDS.iter().any(|(f, _)| *f == field);
+            let platform = field.strip_suffix("_token").and_then(known_platform);
+            if !is_field && platform.is_none() {
+                continue;
+            }
+            let Some(value) = inline.or_else(|| iter.next().cloned()) else {
+                self.warnings.push(format!("Ignored --{}: missing value", name));
+                continue;
+            };
+            match platform {
+                Some(platform) if !is_field => {
+                    self.set_platform_token(platform, &value, ConfigSource::CommandLine)
+                }
+                _ => self.set(&field, &value, ConfigSource::CommandLine),
+            }
+        }
+    }
+
+    fn apply_overrides(&mut self, overrides: Map<String, Value>) {
+        for (k, v) in overrides {
+            self.sources.insert(format!("/{}", k), ConfigSource::Override);
+            self.doc.insert(k, v);
+        }
+    }
+}
+
+/// Resolves the config OpenClaw will actually run with: defaults, then
+/// `openclaw.json`, then `OPENCLAW_*` environment variables, then command-line
+/// flags, then `overrides`.
+pub fn resolve_effective_config(overrides: Map<String, Value>) -> Result<EffectiveConfig, String> {
+    let defaults = match serde_json::to_value(OpenClawConfig::default()) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    let mut layers = Layers {
+        sources: defaults
+            .keys()
+            .map(|k| (format!("/{}", k), ConfigSource::Default))
+            .collect(),
+        doc: defaults,
+        warnings: Vec::new(),
+    };
+
+    if let Some(Value::Object(file)) = load_stored_document()? {
+        layers.apply_file(file);
+    }
+    layers.apply_env();
+    layers.apply_args(&std::env::args().skip(1).collect::<Vec<_>>());
+    layers.apply_overrides(overrides);
+
+    let mut config: OpenClawConfig = serde_json::from_value(Value::Object(layers.doc))
+        .map_err(|e| format!("Invalid effective config: {}", e))?;
+    resolve_secrets(&mut config)?;
+
+    for warning in &layers.warnings {
+        log::warn!(warning:% = warning; "config override ignored");
+    }
+    Ok(EffectiveConfig {
+        config,
+        sources: layers.sources,
+        warnings: layers.warnings,
+    })
+}
+
+#[tauri::command]
+pub async fn get_effective_config(
+    overrides: Option<Map<String, Value>>,
+) -> Result<EffectiveConfig, String> {
+    resolve_effective_config(overrides.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempHome;
+
+    fn layers(file: Value) -> Layers {
+        let mut layers = Layers {
+            doc: Map::new(),
+            sources: BTreeMap::new(),
+            warnings: Vec::new(),
+        };
+        if let Value::Object(file) = file {
+            layers.apply_file(file);
+        }
+        layers
+    }
+
+    #[test]
+    fn only_known_platforms_take_env_tokens() {
+        let _home = TempHome::new();
+        std::env::set_var("OPENCLAW_GATEWAY_TOKEN", "gateway-secret");
+        std::env::set_var("OPENCLAW_TELEGRAM_TOKEN", "123:abc");
+
+        let mut layers = layers(serde_json::json!({
+            "platforms": [{"platform": "Telegram", "bot_token": "old"}]
+        }));
+        layers.apply_env();
+        std::env::remove_var("OPENCLAW_GATEWAY_TOKEN");
+        std::env::remove_var("OPENCLAW_TELEGRAM_TOKEN");
+
+        let platforms = layers.doc["platforms"].as_array().unwrap();
+        assert_eq!(platforms.len(), 1, "{:?}", platforms);
+        assert_eq!(platforms[0]["bot_token"], "123:abc");
+        assert_eq!(layers.sources["/platforms/0/bot_token"], ConfigSource::Environment);
+        assert!(layers.warnings.is_empty(), "{:?}", layers.warnings);
+    }
+
+    #[test]
+    fn only_known_platforms_take_token_flags() {
+        let mut layers = layers(serde_json::json!({}));
+        let args = ["--gateway-token", "x", "--discord-token=abc"].map(String::from);
+        layers.apply_args(&args);
+
+        let platforms = layers.doc["platforms"].as_array().unwrap();
+        assert_eq!(platforms.len(), 1);
+        assert_eq!(platforms[0]["platform"], "discord");
+        assert_eq!(platforms[0]["bot_token"], "abc");
+    }
+}
@@ -1,6 +1,10 @@
 use serde::Serialize;
 use std::process::Command;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
+
+use super::config::OpenClawConfig;
+use super::config_store::{get_openclaw_dir, ConfigWrite};
+use super::logging::{self, run_command};
 
 #[derive(Clone, Serialize)]
 pub struct InstallStep {
@@ -11,7 +15,8 @@ pub struct InstallStep {
 }
 
 fn emit_step(app: &AppHandle, id: &str, status: &str, message: &str, log: Option<String>) {
-    let _ = app.emit(
+    logging::emit_with_summary(
+        app,
         "install-step",
         InstallStep {
             id: id.to_string(),
@@ -19,14 +24,15 @@ fn emit_step(app: &AppHandle, id: &str, status: &str, message: &str, log: Option
             message: message.to_string(),
             log,
         },
+        &format!("{} {}", id, status),
     );
 }
 
 fn dev_mode_output() -> std::io::Result<std::process::Output> {
-    Command::new("echo").arg("dev mode: skipped").output()
+    run_command(Command::new("echo").arg("dev mode: skipped"))
 }
 
-fn find_npm() -> String {
+pub fn find_npm() -> String {
     if cfg!(target_os = "windows") {
         let program_files = std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
         let npm_path = format!("{}\\nodejs\\npm.cmd", program_files);
@@ -37,7 +43,7 @@ fn find_npm() -> String {
     "npm".to_string()
 }
 
-fn find_openclaw() -> String {
+pub fn find_openclaw() -> String {
     if cfg!(target_os = "windows") {
         let appdata = std::env::var("APPDATA").unwrap_or_default();
         let openclaw_path = format!("{}\\npm\\openclaw.cmd", appdata);
@@ -80,9 +86,7 @@ fn create_desktop_shortcut(target: &str, name: &str) -> Result<(), String> {
         lnk_path.to_string_lossy().replace('\'', "''"),
         target.replace('\'', "''")
     );
-    Command::new("powershell")
-        .args(["-NoProfile", "-Command", &ps_script])
-        .output()
+    run_command(Command::new("powershell").args(["-NoProfile", "-Command", &ps_script]))
         .map_err(|e| format!("Failed to create shortcut: {}", e))?;
     Ok(())
 }
@@ -102,17 +106,16 @@ pub async fn install_dependency(
 
     let output = if dep_id == "nodejs" {
         if cfg!(target_os = "windows") {
-            Command::new("msiexec")
-                .args(["/i", &installer_path, "/qn", "/norestart"])
-                .output()
+            run_command(Command::new("msiexec").args(["/i", &installer_path, "/qn", "/norestart"]))
         } else {
             dev_mode_output()
         }
     } else if dep_id == "docker" {
         if cfg!(target_os = "windows") {
-            Command::new(&installer_path)
-                .args(["install", "--quiet", "--accept-license"])
-                .output()
+            run_command(
+                Command::new(&installer_path)
+                    .args(["install", "--quiet", "--accept-license"]),
+            )
         } else {
             dev_mode_output()
         }
@@ -179,9 +182,7 @@ async fn install_openclaw_npm(app: &AppHandle, use_mirror: bool) -> Result<(), S
         args.push(&registry_arg);
     }
 
-    let output = Command::new(&npm)
-        .args(&args)
-        .output()
+    let output = run_command(Command::new(&npm).args(&args))
         .map_err(|e| {
             let msg = format!("Failed to run npm: {}", e);
             emit_step(app, "npm_install", "error", &msg, None);
@@ -203,9 +204,7 @@ async fn install_openclaw_npm(app: &AppHandle, use_mirror: bool) -> Result<(), S
     emit_step(app, "verify_version", "running", "Verifying installation...", None);
 
     let openclaw = find_openclaw();
-    let ver_output = Command::new(&openclaw)
-        .arg("--version")
-        .output();
+    let ver_output = run_command(Command::new(&openclaw).arg("--version"));
 
     match ver_output {
         Ok(out) if out.status.success() => {
@@ -221,27 +220,24 @@ async fn install_openclaw_npm(app: &AppHandle, use_mirror: bool) -> Result<(), S
     // Step 3: Write minimal config
     emit_step(app, "write_config", "running", "Writing configuration...", None);
 
-    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
-    let config_dir = home.join(".openclaw");
-    std::fs::create_dir_all(&config_dir)
-        .map_err(|e| format!("Failed to create config dir: {}", e))?;
-
-    let config_path = config_dir.join("openclaw.json");
-    if !config_path.exists() {
-        let default_config = r#"{
-  "agent": {}
-}"#;
-        std::fs::write(&config_path, default_config)
-            .map_err(|e| format!("Failed to write config: {}", e))?;
+    {
+        let mut write = ConfigWrite::begin(None)?;
+        if write.revision().is_none() {
+            let mut default_config = OpenClawConfig::default();
+            default_config
+                .extra
+                .insert("agent".to_string(), serde_json::json!({}));
+            let json = serde_json::to_string_pretty(&default_config)
+                .map_err(|e| format!("Failed to serialize config: {}", e))?;
+            write.commit(&json)?;
+        }
     }
     emit_step(app, "write_config", "done", "Configuration saved", None);
 
     // Step 4: Start Gateway
     emit_step(app, "start_gateway", "running", "Starting Gateway service...", None);
 
-    let gw_result = Command::new(&openclaw)
-        .args(["gateway", "start"])
-        .output();
+    let gw_result = run_command(Command::new(&openclaw).args(["gateway", "start"]));
 
     match gw_result {
         Ok(out) => {
@@ -290,12 +286,16 @@ async fn install_openclaw_npm(app: &AppHandle, use_mirror: bool) -> Result<(), S
     Ok(())
 }
 
+pub fn docker_dir() -> Result<std::path::PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    Ok(home.join("openclaw"))
+}
+
 async fn install_openclaw_docker(app: &AppHandle) -> Result<(), String> {
     // Step 1: Create directory
     emit_step(app, "docker_setup", "running", "Setting up Docker environment...", None);
 
-    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
-    let openclaw_dir = home.join("openclaw");
+    let openclaw_dir = docker_dir()?;
     std::fs::create_dir_all(&openclaw_dir)
         .map_err(|e| format!("Failed to create directory: {}", e))?;
 
@@ -311,11 +311,12 @@ services:
       - "18789:18789"
       - "18791:18791"
     volumes:
-      - ~/.openclaw:/root/.openclaw
+      - {state_dir}:/root/.openclaw
       - ./workspace:/root/openclaw/workspace
     environment:
       - NODE_ENV=production
-"#;
+"#
+    .replace("{state_dir}", &get_openclaw_dir()?.to_string_lossy());
     std::fs::write(&compose_path, compose_content)
         .map_err(|e| format!("Failed to write docker-compose.yml: {}", e))?;
 
@@ -324,10 +325,11 @@ services:
     // Step 3: docker compose up
     emit_step(app, "docker_start", "running", "Starting Docker containers...", None);
 
-    let output = Command::new("docker")
-        .args(["compose", "up", "-d"])
-        .current_dir(&openclaw_dir)
-        .output()
+    let output = run_command(
+        Command::new("docker")
+            .args(["compose", "up", "-d"])
+            .current_dir(&openclaw_dir),
+    )
         .map_err(|e| {
             let msg = format!("Failed to run docker compose: {}", e);
             emit_step(app, "docker_start", "error", &msg, None);
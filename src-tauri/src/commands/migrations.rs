@@ -0,0 +1,353 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+
+use super::config::PlatformEntry;
+use super::config_store::{get_config_path, ConfigWrite};
+use super::secrets::{delete_secret, secret_names};
+
+/// Schema version written by this installer. Bump it together with a new
+/// entry at the end of `MIGRATIONS`.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+struct Migration {
+    /// Version this migration upgrades from; it produces `from + 1`.
+    from: u32,
+    description: &'static str,
+    apply: fn(&mut Map<String, Value>) -> Vec<String>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "Normalize unversioned installer configs",
+        apply: migrate_v0_to_v1,
+    },
+    Migration {
+        from: 1,
+        description: "Move platform tokens to typed platform settings",
+        apply: migrate_v1_to_v2,
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedMigration {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub description: String,
+    pub changes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub applied: Vec<AppliedMigration>,
+    /// Backup of the pre-migration file, when the migration was written to disk.
+    pub backup: Option<String>,
+}
+
+impl MigrationReport {
+    pub fn changed(&self) -> bool {
+        !self.applied.is_empty()
+    }
+}
+
+pub fn schema_version(doc: &Value) -> u32 {
+    doc.get("schema_version")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// Upgrades `doc` in place to `CURRENT_SCHEMA_VERSION`, running every
+/// migration between the document's version and the current one in order.
+pub fn migrate_document(doc: &mut Value) -> Result<MigrationReport, String> {
+    let from_version = schema_version(doc);
+    if from_version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "Config uses schema version {} but this installer only understands up to {}. Please update the installer.",
+            from_version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+    let map = doc
+        .as_object_mut()
+        .ok_or("Config must be a JSON object")?;
+
+    let mut applied = Vec::new();
+    let mut version = from_version;
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= from_version) {
+        debug_assert_eq!(migration.from, version, "migrations must be contiguous");
+        let changes = (migration.apply)(map);
+        version = migration.from + 1;
+        map.insert("schema_version".to_string(), Value::from(version));
+        applied.push(AppliedMigration {
+            from_version: migration.from,
+            to_version: version,
+            description: migration.description.to_string(),
+            changes,
+        });
+    }
+
+    Ok(MigrationReport {
+        from_version,
+        to_version: version,
+        applied,
+        backup: None,
+    })
+}
+
+/// v0 covers the three shapes older installers wrote: `{"agent": {}}` from the
+/// npm install, the flat settings file, and the troubleshooter's reset default.
+fn migrate_v0_to_v1(map: &mut Map<String, Value>) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    for key in ["model_provider", "model_name", "api_key", "api_endpoint"] {
+        let empty = match map.get(key) {
+            Some(Value::String(s)) => s.trim().is_empty(),
+            Some(Value::Null) => true,
+            _ => false,
+        };
+        if empty {
+            map.remove(key);
+            changes.push(format!("Removed empty {}", key));
+        }
+    }
+
+    match map.get("gateway_port").cloned() {
+        Some(Value::Number(_)) => {}
+        Some(Value::String(s)) => match s.trim().parse::<u16>() {
+            Ok(port) => {
+                map.insert("gateway_port".to_string(), Value::from(port));
+                changes.push(format!("Converted gateway_port \"{}\" to a number", s));
+            }
+            Err(_) => {
+                map.insert("gateway_port".to_string(), Value::from(18789));
+                changes.push(format!("Reset invalid gateway_port \"{}\" to the default 18789", s));
+            }
+        },
+        _ => {
+            map.insert("gateway_port".to_string(), Value::from(18789));
+            changes.push("Added default gateway_port 18789".to_string());
+        }
+    }
+
+    match map.get("platforms") {
+        Some(Value::Array(_)) => {}
+        _ => {
+            map.insert("platforms".to_string(), Value::Array(Vec::new()));
+            changes.push("Added empty platforms list".to_string());
+        }
+    }
+
+    if !map.contains_key("agent") {
+        map.insert("agent".to_string(), Value::Object(Map::new()));
+        changes.push("Added agent block".to_string());
+    }
+
+    changes
+}
+
+/// v1 gave every platform a single `token`. Platforms with typed settings
+/// keep it under their own field name; WhatsApp pairs instead, so its old
+/// token is dropped.
+fn migrate_v1_to_v2(map: &mut Map<String, Value>) -> Vec<String> {
+    let mut changes = Vec::new();
+    let Some(Value::Array(platforms)) = map.get_mut("platforms") else {
+        return changes;
+    };
+    for entry in platforms.iter_mut().filter_map(|e| e.as_object_mut()) {
+        let Some(platform) = entry.get("platform").and_then(|p| p.as_str()).map(str::to_string) else {
+            continue;
+        };
+        if platform.eq_ignore_ascii_case("whatsapp") {
+            if entry.remove("token").is_some() {
+                changes.push("Dropped unused whatsapp token".to_string());
+            }
+            continue;
+        }
+        let Some(field) = PlatformEntry::token_field(&platform).filter(|f| *f != "token") else {
+            continue;
+        };
+        if entry.contains_key(field) {
+            continue;
+        }
+        if let Some(token) = entry.remove("token") {
+            entry.insert(field.to_string(), token);
+            changes.push(format!("Renamed {} token to {}", platform, field));
+        }
+    }
+    changes
+}
+
+/// Upgrades `openclaw.json` on disk. Runs at startup; the command lets the UI
+/// preview or repeat it.
+///
+/// Secrets only the old document referenced are deleted straight away rather
+/// than kept for the backup: the migration drops them for good (the legacy
+/// WhatsApp token), and loading the backup would drop them again.
+pub fn upgrade_stored_config(dry_run: bool) -> Result<MigrationReport, String> {
+    let mut write = ConfigWrite::begin(None)?;
+    let path = get_config_path()?;
+    if !path.exists() {
+        return Ok(MigrationReport {
+            from_version: CURRENT_SCHEMA_VERSION,
+            to_version: CURRENT_SCHEMA_VERSION,
+            applied: Vec::new(),
+            backup: None,
+        });
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read config: {}", e))?;
+    let original: Value =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse config: {}", e))?;
+    let mut doc = original.clone();
+    let mut report = migrate_document(&mut doc)?;
+
+    if report.changed() && !dry_run {
+        let json = serde_json::to_string_pretty(&doc)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        let backup = write.commit(&json)?;
+        report.backup = backup.map(|p| p.to_string_lossy().to_string());
+        log::info!(from = report.from_version, to = report.to_version; "config migrated");
+
+        let kept = secret_names(&doc);
+        for name in secret_names(&original).difference(&kept) {
+            if let Err(e) = delete_secret(name) {
+                log::warn!(secret:% = name, error:% = e; "failed to delete secret dropped by migration");
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn migrate_config(dry_run: Option<bool>) -> Result<MigrationReport, String> {
+    upgrade_stored_config(dry_run.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::secrets::{load_secret, store_secret};
+    use crate::commands::test_support::TempHome;
+    use serde_json::json;
+
+    fn apply(migration: fn(&mut Map<String, Value>) -> Vec<String>, doc: Value) -> (Value, Vec<String>) {
+        let Value::Object(mut map) = doc else { unreachable!() };
+        let changes = migration(&mut map);
+        (Value::Object(map), changes)
+    }
+
+    #[test]
+    fn v0_fills_in_the_npm_install_shape() {
+        let (doc, changes) = apply(migrate_v0_to_v1, json!({"agent": {}}));
+        assert_eq!(doc, json!({"agent": {}, "gateway_port": 18789, "platforms": []}));
+        assert_eq!(changes, ["Added default gateway_port 18789", "Added empty platforms list"]);
+    }
+
+    #[test]
+    fn v0_cleans_up_flat_settings() {
+        let (doc, changes) = apply(
+            migrate_v0_to_v1,
+            json!({"model_provider": "openai", "model_name": " ", "api_key": null, "gateway_port": "8080", "platforms": []}),
+        );
+        assert_eq!(
+            doc,
+            json!({"model_provider": "openai", "gateway_port": 8080, "platforms": [], "agent": {}})
+        );
+        assert_eq!(
+            changes,
+            [
+                "Removed empty model_name",
+                "Removed empty api_key",
+                "Converted gateway_port \"8080\" to a number",
+                "Added agent block",
+            ]
+        );
+    }
+
+    #[test]
+    fn v0_reports_an_unparsable_port_as_a_reset() {
+        let (doc, changes) = apply(migrate_v0_to_v1, json!({"gateway_port": "auto", "platforms": [], "agent": {}}));
+        assert_eq!(doc["gateway_port"], 18789);
+        assert_eq!(changes, ["Reset invalid gateway_port \"auto\" to the default 18789"]);
+    }
+
+    #[test]
+    fn v1_moves_tokens_to_typed_fields() {
+        let (doc, changes) = apply(
+            migrate_v1_to_v2,
+            json!({"platforms": [
+                {"platform": "telegram", "token": "111:bot"},
+                {"platform": "discord", "token": "old", "bot_token": "kept"},
+                {"platform": "WhatsApp", "token": "unused"},
+                {"platform": "matrix", "token": "generic"}
+            ]}),
+        );
+        assert_eq!(
+            doc["platforms"],
+            json!([
+                {"platform": "telegram", "bot_token": "111:bot"},
+                {"platform": "discord", "token": "old", "bot_token": "kept"},
+                {"platform": "WhatsApp"},
+                {"platform": "matrix", "token": "generic"}
+            ])
+        );
+        assert_eq!(changes, ["Renamed telegram token to bot_token", "Dropped unused whatsapp token"]);
+    }
+
+    #[test]
+    fn migrations_chain_up_to_the_current_version() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.from, i as u32);
+        }
+        assert_eq!(MIGRATIONS.len() as u32, CURRENT_SCHEMA_VERSION);
+
+        let mut doc = json!({"platforms": [{"platform": "telegram", "token": "111:bot"}]});
+        let report = migrate_document(&mut doc).unwrap();
+        assert_eq!((report.from_version, report.to_version), (0, CURRENT_SCHEMA_VERSION));
+        let steps: Vec<_> = report.applied.iter().map(|m| (m.from_version, m.to_version)).collect();
+        let expected: Vec<_> = (0..CURRENT_SCHEMA_VERSION).map(|v| (v, v + 1)).collect();
+        assert_eq!(steps, expected);
+        assert_eq!(doc["schema_version"], CURRENT_SCHEMA_VERSION);
+        assert_eq!(doc["platforms"][0]["bot_token"], "111:bot");
+
+        let again = migrate_document(&mut doc).unwrap();
+        assert!(!again.changed());
+
+        let mut newer = json!({"schema_version": CURRENT_SCHEMA_VERSION + 1});
+        assert!(migrate_document(&mut newer).is_err());
+    }
+
+    #[test]
+    fn legacy_whatsapp_token_is_deleted_from_the_store() {
+        let _home = TempHome::new();
+        store_secret("api_key", "sk-kept").unwrap();
+        store_secret("platform.whatsapp.token", "legacy").unwrap();
+        let path = get_config_path().unwrap();
+        fs::write(
+            &path,
+            json!({
+                "schema_version": 1,
+                "api_key": "secret:api_key",
+                "gateway_port": 18789,
+                "platforms": [{"platform": "whatsapp", "token": "secret:platform.whatsapp.token"}],
+                "agent": {}
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        upgrade_stored_config(true).unwrap();
+        assert_eq!(load_secret("platform.whatsapp.token").unwrap().as_deref(), Some("legacy"));
+
+        let report = upgrade_stored_config(false).unwrap();
+        assert_eq!(report.to_version, CURRENT_SCHEMA_VERSION);
+        let doc: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(doc["platforms"], json!([{"platform": "whatsapp"}]));
+        assert_eq!(load_secret("platform.whatsapp.token").unwrap(), None);
+        assert_eq!(load_secret("api_key").unwrap().as_deref(), Some("sk-kept"));
+    }
+}
@@ -1,8 +1,31 @@
+pub mod api_test;
+pub mod backup;
+pub mod catalog;
+pub mod chat;
 pub mod config;
+pub mod config_store;
+pub mod cost;
 pub mod detect;
+pub mod discovery;
 pub mod doctor;
 pub mod download;
+pub mod effective;
 pub mod install;
+pub mod logging;
+pub mod migrations;
 pub mod models;
+pub mod ollama;
+pub mod platforms;
+pub mod presets;
+pub mod profiles;
+pub mod providers;
+pub mod secrets;
 pub mod service;
+pub mod support;
+#[cfg(test)]
+mod test_support;
+pub mod transfer;
 pub mod troubleshoot;
+pub mod usage;
+pub mod validation;
+pub mod watcher;
@@ -1,5 +1,61 @@
 use serde::{Deserialize, Serialize};
 
+use super::catalog::load_providers;
+
+/// How a provider expects the API key to be sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthStyle {
+    #[default]
+    Bearer,
+    XApiKey,
+    XGoogApiKey,
+    None,
+}
+
+/// Which request dialect a provider's API speaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiStyle {
+    /// `/chat/completions`, authenticated as `auth` says.
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    Anthropic,
+    Gemini,
+    Bedrock,
+    Ollama,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    #[serde(default)]
+    pub context_window: Option<u32>,
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>,
+    /// `streaming`, `tools`, `vision`, `reasoning`.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub pricing: Option<ModelPricing>,
+}
+
+/// List price per million tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    /// ISO 4217 code, e.g. `USD` or `CNY`.
+    pub currency: String,
+}
+
+impl ModelInfo {
+    pub fn has(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelProvider {
     pub id: String,
@@ -8,6 +64,14 @@ pub struct ModelProvider {
     pub default_model: String,
     pub supports_streaming: bool,
     pub max_tokens: u32,
+    pub endpoint: Option<String>,
+    pub api: ApiStyle,
+    pub auth: AuthStyle,
+    pub docs_url: Option<String>,
+    /// Limits and capabilities for each entry in `models`.
+    pub model_details: Vec<ModelInfo>,
+    /// Defined by the user rather than the catalog.
+    pub custom: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,84 +97,30 @@ impl Default for ModelParameters {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelPreset {
     pub name: String,
     pub description: String,
     pub parameters: ModelParameters,
+    /// Provider the preset is tuned for; `None` suits any provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Shipped with the installer and read-only.
+    #[serde(default)]
+    pub builtin: bool,
+    /// Translation key of a built-in preset's name and description, e.g.
+    /// `presets.creative`; the fields above hold the English text.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub i18n_key: Option<String>,
 }
 
-#[tauri::command]
-pub async fn get_available_providers() -> Result<Vec<ModelProvider>, String> {
-    Ok(vec![
-        ModelProvider {
-            id: "alibaba".to_string(),
-            name: "阿里云百炼 / 通义千问".to_string(),
-            models: vec![
-                "qwen-max".to_string(),
-                "qwen-plus".to_string(),
-                "qwen-turbo".to_string(),
-            ],
-            default_model: "qwen-plus".to_string(),
-            supports_streaming: true,
-            max_tokens: 8192,
-        },
-        ModelProvider {
-            id: "deepseek".to_string(),
-            name: "深度求索 DeepSeek".to_string(),
-            models: vec![
-                "deepseek-chat".to_string(),
-                "deepseek-coder".to_string(),
-            ],
-            default_model: "deepseek-chat".to_string(),
-            supports_streaming: true,
-            max_tokens: 4096,
-        },
-        ModelProvider {
-            id: "zhipu".to_string(),
-            name: "智谱 AI / GLM".to_string(),
-            models: vec![
-                "glm-4".to_string(),
-                "glm-4-air".to_string(),
-                "glm-3-turbo".to_string(),
-            ],
-            default_model: "glm-4".to_string(),
-            supports_streaming: true,
-            max_tokens: 8192,
-        },
-        ModelProvider {
-            id: "anthropic".to_string(),
-            name: "Anthropic Claude".to_string(),
-            models: vec![
-                "claude-3-5-sonnet-20241022".to_string(),
-                "claude-3-opus-20240229".to_string(),
-                "claude-3-haiku-20240307".to_string(),
-            ],
-            default_model: "claude-3-5-sonnet-20241022".to_string(),
-            supports_streaming: true,
-            max_tokens: 8192,
-        },
-        ModelProvider {
-            id: "openai".to_string(),
-            name: "OpenAI".to_string(),
-            models: vec![
-                "gpt-4-turbo".to_string(),
-                "gpt-4".to_string(),
-                "gpt-3.5-turbo".to_string(),
-            ],
-            default_model: "gpt-4-turbo".to_string(),
-            supports_streaming: true,
-            max_tokens: 4096,
-        },
-    ])
-}
-
-#[tauri::command]
-pub async fn get_model_presets() -> Result<Vec<ModelPreset>, String> {
-    Ok(vec![
+pub fn builtin_presets() -> Vec<ModelPreset> {
+    vec![
         ModelPreset {
             name: "Creative".to_string(),
-            description: "高创造力，适合创意写作和头脑风暴".to_string(),
+            description: "High creativity for creative writing and brainstorming".to_string(),
             parameters: ModelParameters {
                 temperature: 1.0,
                 max_tokens: 4096,
@@ -119,15 +129,23 @@ pub async fn get_model_presets() -> Result<Vec<ModelPreset>, String> {
                 presence_penalty: 0.5,
                 stream: true,
             },
+            provider: None,
+            model: None,
+            builtin: true,
+            i18n_key: Some("presets.creative".to_string()),
         },
         ModelPreset {
             name: "Balanced".to_string(),
-            description: "平衡模式，适合日常对话".to_string(),
+            description: "Balanced for everyday conversation".to_string(),
             parameters: ModelParameters::default(),
+            provider: None,
+            model: None,
+            builtin: true,
+            i18n_key: Some("presets.balanced".to_string()),
         },
         ModelPreset {
             name: "Precise".to_string(),
-            description: "精确模式，适合技术问答和代码生成".to_string(),
+            description: "Precise answers for technical questions and code".to_string(),
             parameters: ModelParameters {
                 temperature: 0.3,
                 max_tokens: 4096,
@@ -136,52 +154,266 @@ pub async fn get_model_presets() -> Result<Vec<ModelPreset>, String> {
                 presence_penalty: 0.0,
                 stream: true,
             },
+            provider: None,
+            model: None,
+            builtin: true,
+            i18n_key: Some("presets.precise".to_string()),
         },
-    ])
+    ]
 }
 
 #[tauri::command]
-pub async fn validate_model_parameters(params: ModelParameters) -> Result<bool, String> {
-    if params.temperature < 0.0 || params.temperature > 2.0 {
-        return Err("Temperature must be between 0.0 and 2.0".to_string());
+pub async fn get_available_providers() -> Result<Vec<ModelProvider>, String> {
+    Ok(load_providers())
+}
+
+/// Inclusive bounds for a numeric parameter.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ParameterRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ParameterRange {
+    const fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
     }
-    if params.max_tokens < 1 || params.max_tokens > 32768 {
-        return Err("Max tokens must be between 1 and 32768".to_string());
+
+    fn contains(&self, value: f32) -> bool {
+        value >= self.min && value <= self.max
     }
-    if params.top_p < 0.0 || params.top_p > 1.0 {
-        return Err("Top P must be between 0.0 and 1.0".to_string());
+}
+
+/// What a provider and model accept. `None` marks a parameter the model
+/// rejects or ignores, which only its neutral value passes.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParameterLimits {
+    pub temperature: ParameterRange,
+    pub max_tokens: u32,
+    pub top_p: Option<ParameterRange>,
+    pub frequency_penalty: Option<ParameterRange>,
+    pub presence_penalty: Option<ParameterRange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParameterViolation {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParameterValidation {
+    pub valid: bool,
+    pub violations: Vec<ParameterViolation>,
+    pub limits: ParameterLimits,
+}
+
+/// Limit for `max_tokens` when neither model nor provider is known.
+const DEFAULT_MAX_TOKENS: u32 = 32768;
+const PENALTY_RANGE: ParameterRange = ParameterRange::new(-2.0, 2.0);
+
+/// Limits from the catalog entry for `model`, falling back to the provider
+/// and then to the OpenAI ranges most APIs share.
+pub fn parameter_limits(provider: Option<&str>, model: Option<&str>) -> ParameterLimits {
+    let catalog = load_providers();
+    let entry = provider.and_then(|id| catalog.iter().find(|p| p.id == id));
+    let info = model.and_then(|m| {
+        entry
+            .into_iter()
+            .chain(catalog.iter())
+            .find_map(|p| p.model_details.iter().find(|d| d.id == m))
+    });
+
+    let provider = provider.unwrap_or_default();
+    let model = model.unwrap_or_default();
+    let claude = provider == "anthropic" || model.contains("claude");
+    let reasoning = info.is_some_and(|i| i.has("reasoning"));
+    // Anthropic and the Bedrock Converse API have no penalties
+    let penalties = !(claude || provider == "bedrock" || reasoning);
+
+    ParameterLimits {
+        temperature: if claude {
+            ParameterRange::new(0.0, 1.0)
+        } else {
+            ParameterRange::new(0.0, 2.0)
+        },
+        max_tokens: info
+            .and_then(|i| i.max_output_tokens)
+            .or(entry.map(|p| p.max_tokens))
+            .unwrap_or(DEFAULT_MAX_TOKENS),
+        // Reasoning models sample on their own terms
+        top_p: (!reasoning).then_some(ParameterRange::new(0.0, 1.0)),
+        frequency_penalty: penalties.then_some(PENALTY_RANGE),
+        presence_penalty: penalties.then_some(PENALTY_RANGE),
     }
-    if params.frequency_penalty < -2.0 || params.frequency_penalty > 2.0 {
-        return Err("Frequency penalty must be between -2.0 and 2.0".to_string());
+}
+
+/// Every way `params` breaks `limits`.
+pub fn check_parameters(params: &ModelParameters, limits: &ParameterLimits) -> Vec<ParameterViolation> {
+    let mut violations = Vec::new();
+    let mut check = |field: &str, label: &str, value: f32, range: Option<ParameterRange>, neutral: f32| match range {
+        Some(range) if !range.contains(value) => violations.push(ParameterViolation {
+            field: field.to_string(),
+            message: format!("{} must be between {:.1} and {:.1}", label, range.min, range.max),
+        }),
+        None if value != neutral => violations.push(ParameterViolation {
+            field: field.to_string(),
+            message: format!("{} is not supported by this model", label),
+        }),
+        _ => {}
+    };
+    check("temperature", "Temperature", params.temperature, Some(limits.temperature), 1.0);
+    check("top_p", "Top P", params.top_p, limits.top_p, 1.0);
+    check("frequency_penalty", "Frequency penalty", params.frequency_penalty, limits.frequency_penalty, 0.0);
+    check("presence_penalty", "Presence penalty", params.presence_penalty, limits.presence_penalty, 0.0);
+
+    if params.max_tokens < 1 || params.max_tokens > limits.max_tokens {
+        violations.push(ParameterViolation {
+            field: "max_tokens".to_string(),
+            message: format!("Max tokens must be between 1 and {}", limits.max_tokens),
+        });
     }
-    if params.presence_penalty < -2.0 || params.presence_penalty > 2.0 {
-        return Err("Presence penalty must be between -2.0 and 2.0".to_string());
+    violations
+}
+
+/// `params` moved to the nearest values `limits` allows; unsupported
+/// parameters are reset to their neutral value.
+pub fn clamp_parameters(params: ModelParameters, limits: &ParameterLimits) -> ModelParameters {
+    let clamp = |value: f32, range: Option<ParameterRange>, neutral: f32| match range {
+        Some(range) => value.clamp(range.min, range.max),
+        None => neutral,
+    };
+    ModelParameters {
+        temperature: clamp(params.temperature, Some(limits.temperature), 1.0),
+        max_tokens: params.max_tokens.clamp(1, limits.max_tokens),
+        top_p: clamp(params.top_p, limits.top_p, 1.0),
+        frequency_penalty: clamp(params.frequency_penalty, limits.frequency_penalty, 0.0),
+        presence_penalty: clamp(params.presence_penalty, limits.presence_penalty, 0.0),
+        stream: params.stream,
     }
-    Ok(true)
 }
 
-#[derive(Debug, Serialize)]
-pub struct ModelUsageStats {
-    pub provider: String,
-    pub model: String,
-    pub total_requests: u64,
-    pub total_tokens: u64,
-    pub avg_response_time_ms: u64,
-    pub success_rate: f32,
+/// Checks `params` against the limits of `provider`/`model` and reports every
+/// violation. Without a provider or model the generic ranges apply.
+#[tauri::command]
+pub async fn validate_model_parameters(
+    params: ModelParameters,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<ParameterValidation, String> {
+    let limits = parameter_limits(provider.as_deref(), model.as_deref());
+    let violations = check_parameters(&params, &limits);
+    Ok(ParameterValidation {
+        valid: violations.is_empty(),
+        violations,
+        limits,
+    })
 }
 
 #[tauri::command]
-pub async fn get_model_usage_stats() -> Result<Vec<ModelUsageStats>, String> {
-    // TODO: Implement actual stats tracking
-    // For now, return mock data
-    Ok(vec![
-        ModelUsageStats {
-            provider: "alibaba".to_string(),
-            model: "qwen-plus".to_string(),
-            total_requests: 42,
-            total_tokens: 15234,
-            avg_response_time_ms: 1250,
-            success_rate: 0.98,
-        },
-    ])
+pub async fn clamp_model_parameters(
+    params: ModelParameters,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<ModelParameters, String> {
+    Ok(clamp_parameters(params, &parameter_limits(provider.as_deref(), model.as_deref())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempHome;
+
+    fn params(temperature: f32, top_p: f32, penalty: f32, max_tokens: u32) -> ModelParameters {
+        ModelParameters {
+            temperature,
+            max_tokens,
+            top_p,
+            frequency_penalty: penalty,
+            presence_penalty: penalty,
+            stream: true,
+        }
+    }
+
+    fn fields(violations: &[ParameterViolation]) -> Vec<&str> {
+        violations.iter().map(|v| v.field.as_str()).collect()
+    }
+
+    #[test]
+    fn anthropic_temperature_is_capped_at_one() {
+        let _home = TempHome::new();
+        let anthropic = parameter_limits(Some("anthropic"), Some("claude-3-5-haiku-20241022"));
+        assert_eq!(anthropic.temperature.max, 1.0);
+        assert_eq!(anthropic.max_tokens, 8192);
+        assert_eq!(fields(&check_parameters(&params(1.5, 1.0, 0.0, 1024), &anthropic)), ["temperature"]);
+
+        let openai = parameter_limits(Some("openai"), Some("gpt-4o"));
+        assert_eq!(openai.temperature.max, 2.0);
+        assert!(check_parameters(&params(1.5, 1.0, 0.0, 1024), &openai).is_empty());
+    }
+
+    #[test]
+    fn penalties_are_rejected_where_the_api_has_none() {
+        let _home = TempHome::new();
+        for (provider, model) in [
+            ("bedrock", "amazon.nova-pro-v1:0"),
+            ("openrouter", "anthropic/claude-3.5-sonnet"),
+            ("deepseek", "deepseek-reasoner"),
+        ] {
+            let limits = parameter_limits(Some(provider), Some(model));
+            assert!(limits.frequency_penalty.is_none(), "{}", model);
+            let violations = check_parameters(&params(0.7, 1.0, 0.5, 1024), &limits);
+            assert!(fields(&violations).contains(&"frequency_penalty"), "{}", model);
+            assert!(fields(&violations).contains(&"presence_penalty"), "{}", model);
+            // Neutral values are fine
+            assert!(!fields(&check_parameters(&params(0.7, 1.0, 0.0, 1024), &limits))
+                .contains(&"frequency_penalty"));
+        }
+    }
+
+    #[test]
+    fn reasoning_models_reject_a_non_neutral_top_p() {
+        let _home = TempHome::new();
+        let limits = parameter_limits(Some("deepseek"), Some("deepseek-reasoner"));
+        assert!(limits.top_p.is_none());
+        let violations = check_parameters(&params(0.7, 0.9, 0.0, 1024), &limits);
+        assert_eq!(fields(&violations), ["top_p"]);
+        assert!(violations[0].message.contains("not supported"));
+        assert!(check_parameters(&params(0.7, 1.0, 0.0, 1024), &limits).is_empty());
+    }
+
+    #[test]
+    fn every_violation_is_reported_at_once() {
+        let _home = TempHome::new();
+        let limits = parameter_limits(Some("anthropic"), Some("claude-3-5-haiku-20241022"));
+        let violations = check_parameters(&params(1.2, 1.5, 1.0, 100_000), &limits);
+        assert_eq!(
+            fields(&violations),
+            ["temperature", "top_p", "frequency_penalty", "presence_penalty", "max_tokens"]
+        );
+        assert_eq!(violations[4].message, "Max tokens must be between 1 and 8192");
+    }
+
+    #[test]
+    fn clamping_moves_values_into_range_and_resets_unsupported_ones() {
+        let _home = TempHome::new();
+        let anthropic = parameter_limits(Some("anthropic"), Some("claude-3-5-haiku-20241022"));
+        let clamped = clamp_parameters(params(1.8, 1.5, 1.0, 0), &anthropic);
+        assert_eq!(clamped.temperature, 1.0);
+        assert_eq!(clamped.top_p, 1.0);
+        assert_eq!(clamped.frequency_penalty, 0.0);
+        assert_eq!(clamped.presence_penalty, 0.0);
+        assert_eq!(clamped.max_tokens, 1);
+        assert!(check_parameters(&clamped, &anthropic).is_empty());
+
+        let openai = parameter_limits(Some("openai"), Some("gpt-4o"));
+        let clamped = clamp_parameters(params(-1.0, 0.5, -3.0, 1_000_000), &openai);
+        assert_eq!(clamped.temperature, 0.0);
+        assert_eq!(clamped.top_p, 0.5);
+        assert_eq!(clamped.frequency_penalty, -2.0);
+        assert_eq!(clamped.max_tokens, 16384);
+
+        let reasoning = parameter_limits(Some("deepseek"), Some("deepseek-reasoner"));
+        assert_eq!(clamp_parameters(params(0.7, 0.3, 0.0, 1024), &reasoning).top_p, 1.0);
+    }
 }
@@ -0,0 +1,552 @@
+//! Managing a local Ollama server: install detection, starting and stopping
+//! `ollama serve`, and the model store behind its HTTP API. Every command
+//! accepts an endpoint so it can be pointed at any server, including a stub.
+
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use super::config::read_openclaw_config;
+use super::logging::{emit, run_command, send_request};
+
+pub const PULL_PROGRESS_EVENT: &str = "ollama-pull-progress";
+
+const DEFAULT_ENDPOINT: &str = "http://localhost:11434";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long `ollama serve` gets to start answering.
+const START_TIMEOUT: Duration = Duration::from_secs(15);
+/// Minimum gap between progress events for the same pull phase.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The server this installer started, if any; only it is stopped gracefully.
+static SERVER: Mutex<Option<Child>> = Mutex::new(None);
+
+#[derive(Debug, Serialize)]
+pub struct OllamaStatus {
+    pub installed: bool,
+    /// `ollama --version` output.
+    pub version: Option<String>,
+    pub running: bool,
+    /// Version reported by the server at `endpoint`.
+    pub server_version: Option<String>,
+    pub endpoint: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OllamaModel {
+    pub name: String,
+    pub size: u64,
+    pub modified_at: Option<String>,
+    pub digest: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaPullProgress {
+    pub model: String,
+    /// Ollama's phase text, e.g. `pulling manifest` or `verifying sha256 digest`.
+    pub status: String,
+    pub digest: Option<String>,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OllamaModelCheck {
+    /// Ollama models the config refers to, primary first.
+    pub models: Vec<ConfiguredOllamaModel>,
+    pub running: bool,
+    pub endpoint: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfiguredOllamaModel {
+    pub name: String,
+    pub present: bool,
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<TagEntry>,
+}
+
+#[derive(Deserialize)]
+struct TagEntry {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    modified_at: Option<String>,
+    digest: Option<String>,
+    details: Option<TagDetails>,
+}
+
+#[derive(Deserialize)]
+struct TagDetails {
+    parameter_size: Option<String>,
+    quantization_level: Option<String>,
+}
+
+/// One line of the `/api/pull` NDJSON stream.
+#[derive(Deserialize)]
+struct PullLine {
+    #[serde(default)]
+    status: String,
+    digest: Option<String>,
+    total: Option<u64>,
+    completed: Option<u64>,
+    error: Option<String>,
+}
+
+/// `endpoint` if given, else `OLLAMA_HOST` as Ollama itself reads it, else
+/// the default port on localhost.
+fn resolve_endpoint(endpoint: Option<String>) -> String {
+    let endpoint = endpoint
+        .filter(|e| !e.trim().is_empty())
+        .or_else(|| std::env::var("OLLAMA_HOST").ok().filter(|e| !e.trim().is_empty()))
+        .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string());
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    // OLLAMA_HOST is often just host:port
+    if endpoint.contains("://") {
+        endpoint.to_string()
+    } else {
+        format!("http://{}", endpoint)
+    }
+}
+
+fn client(timeout: Duration) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))
+}
+
+/// `llama3` and `llama3:latest` name the same model.
+pub fn normalize_tag(name: &str) -> String {
+    if name.contains(':') {
+        name.to_string()
+    } else {
+        format!("{}:latest", name)
+    }
+}
+
+async fn server_version(endpoint: &str) -> Option<String> {
+    let resp = send_request(client(PROBE_TIMEOUT).ok()?.get(format!("{}/api/version", endpoint)))
+        .await
+        .ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let body: serde_json::Value = resp.json().await.ok()?;
+    Some(body.get("version").and_then(|v| v.as_str()).unwrap_or_default().to_string())
+}
+
+fn cli_version() -> Option<String> {
+    let output = run_command(Command::new("ollama").arg("--version")).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    // "ollama version is 0.5.7", possibly after a warning about the server
+    text.lines()
+        .rev()
+        .find_map(|l| l.rsplit_once("version is ").map(|(_, v)| v.trim().to_string()))
+        .or_else(|| Some(text.trim().to_string()))
+}
+
+async fn status(endpoint: String) -> OllamaStatus {
+    let version = cli_version();
+    let server_version = server_version(&endpoint).await;
+    OllamaStatus {
+        installed: version.is_some(),
+        version,
+        running: server_version.is_some(),
+        server_version,
+        endpoint,
+    }
+}
+
+async fn fetch_models(endpoint: &str) -> Result<Vec<OllamaModel>, String> {
+    let resp = send_request(client(REQUEST_TIMEOUT)?.get(format!("{}/api/tags", endpoint)))
+        .await
+        .map_err(|e| format!("Cannot reach Ollama at {}: {}", endpoint, e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Ollama returned {}", resp.status()));
+    }
+    let tags: TagsResponse = resp
+        .json()
+        .await
+        .map_err(|e| format!("Invalid model list from Ollama: {}", e))?;
+    Ok(tags
+        .models
+        .into_iter()
+        .map(|m| OllamaModel {
+            name: m.name,
+            size: m.size,
+            modified_at: m.modified_at,
+            digest: m.digest,
+            parameter_size: m.details.as_ref().and_then(|d| d.parameter_size.clone()),
+            quantization: m.details.and_then(|d| d.quantization_level),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn detect_ollama(endpoint: Option<String>) -> Result<OllamaStatus, String> {
+    Ok(status(resolve_endpoint(endpoint)).await)
+}
+
+/// Starts `ollama serve` unless a server already answers at the endpoint,
+/// and waits until it does.
+#[tauri::command]
+pub async fn ollama_start(endpoint: Option<String>) -> Result<OllamaStatus, String> {
+    let endpoint = resolve_endpoint(endpoint);
+    if server_version(&endpoint).await.is_some() {
+        return Ok(status(endpoint).await);
+    }
+
+    let mut cmd = Command::new("ollama");
+    cmd.arg("serve")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(host) = endpoint.split_once("://").map(|(_, h)| h) {
+        cmd.env("OLLAMA_HOST", host);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let child = cmd.spawn().map_err(|e| format!("Failed to start Ollama: {}", e))?;
+    log::info!(pid = child.id(), endpoint:% = endpoint; "ollama serve started");
+    *SERVER.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+
+    let started = Instant::now();
+    while started.elapsed() < START_TIMEOUT {
+        if server_version(&endpoint).await.is_some() {
+            return Ok(status(endpoint).await);
+        }
+        let exited = SERVER
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_mut()
+            .and_then(|c| c.try_wait().ok().flatten());
+        if let Some(code) = exited {
+            return Err(format!("Ollama exited during startup ({})", code));
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+    }
+    Err(format!("Ollama did not respond at {} within {} s", endpoint, START_TIMEOUT.as_secs()))
+}
+
+/// Stops the server this installer started. A server started any other way
+/// (a system service, the desktop app, a terminal) is left alone: killing it
+/// would take it from its owner, and a service manager would restart it.
+#[tauri::command]
+pub async fn ollama_stop() -> Result<(), String> {
+    let Some(mut child) = SERVER.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return Err("Ollama was not started by the installer; stop it where it is managed".to_string());
+    };
+    if child.try_wait().ok().flatten().is_some() {
+        return Err("The Ollama server started by the installer has already exited".to_string());
+    }
+    child.kill().map_err(|e| format!("Failed to stop Ollama: {}", e))?;
+    let _ = child.wait();
+    log::info!("ollama serve stopped");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn ollama_list_models(endpoint: Option<String>) -> Result<Vec<OllamaModel>, String> {
+    fetch_models(&resolve_endpoint(endpoint)).await
+}
+
+/// Pulls `model`, emitting `ollama-pull-progress` as the server streams its
+/// NDJSON status lines. Resolves once the pull succeeded.
+#[tauri::command]
+pub async fn ollama_pull_model(app: AppHandle, model: String, endpoint: Option<String>) -> Result<(), String> {
+    pull_model(&model, &resolve_endpoint(endpoint), |progress| {
+        emit(&app, PULL_PROGRESS_EVENT, progress)
+    })
+    .await
+}
+
+async fn pull_model(
+    model: &str,
+    endpoint: &str,
+    on_progress: impl Fn(OllamaPullProgress),
+) -> Result<(), String> {
+    // Pulls take as long as the download; only the connection is bounded
+    let client = reqwest::Client::builder()
+        .connect_timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let progress = |status: String, line: Option<&PullLine>, done: bool, error: Option<String>| OllamaPullProgress {
+        model: model.to_string(),
+        status,
+        digest: line.and_then(|l| l.digest.clone()),
+        total: line.and_then(|l| l.total),
+        completed: line.and_then(|l| l.completed),
+        done,
+        error,
+    };
+    let fail = |message: String| {
+        on_progress(progress("error".to_string(), None, true, Some(message.clone())));
+        message
+    };
+
+    let resp = send_request(
+        client
+            .post(format!("{}/api/pull", endpoint))
+            .json(&json!({"model": model, "stream": true})),
+    )
+    .await
+    .map_err(|e| fail(format!("Cannot reach Ollama at {}: {}", endpoint, e)))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<PullLine>(&body)
+            .ok()
+            .and_then(|l| l.error)
+            .unwrap_or(body);
+        return Err(fail(format!("Ollama returned {} — {}", status, message)));
+    }
+
+    let mut stream = resp.bytes_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut last_status = String::new();
+    let mut last_emit = Instant::now();
+    let mut succeeded = false;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| fail(format!("Pull interrupted: {}", e)))?;
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            let Ok(line) = serde_json::from_slice::<PullLine>(&line) else {
+                continue;
+            };
+            if let Some(error) = line.error {
+                return Err(fail(format!("Pull failed: {}", error)));
+            }
+            succeeded = line.status == "success";
+            // Phase changes always go out; byte counts are throttled
+            if line.status != last_status || last_emit.elapsed() >= PROGRESS_INTERVAL || succeeded {
+                on_progress(progress(line.status.clone(), Some(&line), succeeded, None));
+                last_status = line.status.clone();
+                last_emit = Instant::now();
+            }
+        }
+    }
+
+    if !succeeded {
+        return Err(fail("Pull ended before Ollama reported success".to_string()));
+    }
+    log::info!(model:% = model; "ollama model pulled");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn ollama_delete_model(model: String, endpoint: Option<String>) -> Result<(), String> {
+    let endpoint = resolve_endpoint(endpoint);
+    let resp = send_request(
+        client(REQUEST_TIMEOUT)?
+            .delete(format!("{}/api/delete", endpoint))
+            .json(&json!({"model": model})),
+    )
+    .await
+    .map_err(|e| format!("Cannot reach Ollama at {}: {}", endpoint, e))?;
+    match resp.status() {
+        s if s.is_success() => {
+            log::info!(model:% = model; "ollama model deleted");
+            Ok(())
+        }
+        reqwest::StatusCode::NOT_FOUND => Err(format!("Model not found: {}", model)),
+        s => Err(format!("Ollama returned {}", s)),
+    }
+}
+
+/// Whether the Ollama models the config uses (primary and fallbacks) are
+/// present on the server. The primary's endpoint is used when it is Ollama.
+#[tauri::command]
+pub async fn check_ollama_model(endpoint: Option<String>) -> Result<OllamaModelCheck, String> {
+    let config = read_openclaw_config().await?;
+    let primary_is_ollama = config.model_provider.as_deref() == Some("ollama");
+    let endpoint = resolve_endpoint(endpoint.or_else(|| {
+        primary_is_ollama
+            .then(|| config.api_endpoint.clone())
+            .flatten()
+    }));
+
+    let mut wanted: Vec<String> = Vec::new();
+    if primary_is_ollama {
+        wanted.extend(config.model_name.clone());
+    }
+    wanted.extend(
+        config
+            .model_fallbacks
+            .iter()
+            .filter(|h| h.provider == "ollama")
+            .map(|h| h.model.clone()),
+    );
+    if wanted.is_empty() {
+        return Err("The config does not use any Ollama model".to_string());
+    }
+
+    let (running, local) = match fetch_models(&endpoint).await {
+        Ok(models) => (true, models.into_iter().map(|m| normalize_tag(&m.name)).collect()),
+        Err(e) => {
+            log::warn!(endpoint:% = endpoint, error:% = e; "ollama model check failed");
+            (false, Vec::new())
+        }
+    };
+    let models = wanted
+        .into_iter()
+        .map(|name| ConfiguredOllamaModel {
+            present: local.contains(&normalize_tag(&name)),
+            name,
+        })
+        .collect();
+    Ok(OllamaModelCheck {
+        models,
+        running,
+        endpoint,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{refused_url, StubResponse, StubServer};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn detect_reports_server_version() {
+        let server = StubServer::start(|r| match r.path.as_str() {
+            "/api/version" => StubResponse::json(200, json!({"version": "0.5.7"})),
+            _ => StubResponse::json(404, json!({})),
+        })
+        .await;
+        let status = detect_ollama(Some(server.url.clone())).await.unwrap();
+        assert!(status.running);
+        assert_eq!(status.server_version.as_deref(), Some("0.5.7"));
+        assert_eq!(status.endpoint, server.url);
+
+        let status = detect_ollama(Some(refused_url().await)).await.unwrap();
+        assert!(!status.running);
+    }
+
+    #[tokio::test]
+    async fn lists_models_with_details() {
+        let server = StubServer::start(|_| {
+            StubResponse::json(
+                200,
+                json!({"models": [{
+                    "name": "llama3:latest",
+                    "size": 4661224676u64,
+                    "digest": "365c0bd3c000",
+                    "details": {"parameter_size": "8.0B", "quantization_level": "Q4_0"}
+                }]}),
+            )
+        })
+        .await;
+        let models = ollama_list_models(Some(format!("{}/", server.url))).await.unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "llama3:latest");
+        assert_eq!(models[0].parameter_size.as_deref(), Some("8.0B"));
+        assert_eq!(models[0].quantization.as_deref(), Some("Q4_0"));
+        assert_eq!(server.requests()[0].path, "/api/tags");
+    }
+
+    #[tokio::test]
+    async fn deletes_models() {
+        let server = StubServer::start(|r| {
+            if r.body.contains("\"llama3\"") {
+                StubResponse::json(200, json!({}))
+            } else {
+                StubResponse::json(404, json!({"error": "model not found"}))
+            }
+        })
+        .await;
+        ollama_delete_model("llama3".to_string(), Some(server.url.clone())).await.unwrap();
+        let request = &server.requests()[0];
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("DELETE", "/api/delete"));
+
+        let err = ollama_delete_model("missing".to_string(), Some(server.url.clone())).await.unwrap_err();
+        assert_eq!(err, "Model not found: missing");
+    }
+
+    #[tokio::test]
+    async fn pull_parses_lines_split_across_chunks() {
+        let server = StubServer::start(|_| {
+            StubResponse::chunked(
+                200,
+                "application/x-ndjson",
+                &[
+                    "{\"status\":\"pulling manifest\"}\n{\"status\":\"pulling 6a0746a1ec1a\",\"digest\":\"sha256:6a07\",",
+                    "\"total\":100,\"completed\":40}\n",
+                    "{\"status\":\"verifying sha256 digest\"}\n{\"status\":\"succ",
+                    "ess\"}\n",
+                ],
+            )
+        })
+        .await;
+        let events = Mutex::new(Vec::new());
+        pull_model("llama3", &server.url, |p| events.lock().unwrap().push(p)).await.unwrap();
+
+        let events = events.into_inner().unwrap();
+        let statuses: Vec<&str> = events.iter().map(|e| e.status.as_str()).collect();
+        assert_eq!(
+            statuses,
+            ["pulling manifest", "pulling 6a0746a1ec1a", "verifying sha256 digest", "success"]
+        );
+        assert_eq!(events[1].total, Some(100));
+        assert_eq!(events[1].completed, Some(40));
+        assert!(events.last().unwrap().done);
+        assert!(server.requests()[0].body.contains("\"model\":\"llama3\""));
+    }
+
+    #[tokio::test]
+    async fn pull_reports_stream_errors() {
+        let server = StubServer::start(|_| {
+            StubResponse::chunked(
+                200,
+                "application/x-ndjson",
+                &["{\"status\":\"pulling manifest\"}\n", "{\"error\":\"pull model manifest: file does not exist\"}\n"],
+            )
+        })
+        .await;
+        let events = Mutex::new(Vec::new());
+        let err = pull_model("nope", &server.url, |p| events.lock().unwrap().push(p)).await.unwrap_err();
+        assert_eq!(err, "Pull failed: pull model manifest: file does not exist");
+        let last = events.into_inner().unwrap().pop().unwrap();
+        assert!(last.done);
+        assert_eq!(last.error.as_deref(), Some(err.as_str()));
+    }
+
+    #[tokio::test]
+    async fn pull_without_success_fails() {
+        let server = StubServer::start(|_| {
+            StubResponse::chunked(200, "application/x-ndjson", &["{\"status\":\"pulling manifest\"}\n"])
+        })
+        .await;
+        let err = pull_model("llama3", &server.url, |_| {}).await.unwrap_err();
+        assert_eq!(err, "Pull ended before Ollama reported success");
+    }
+
+    #[test]
+    fn normalizes_tags() {
+        assert_eq!(normalize_tag("llama3"), "llama3:latest");
+        assert_eq!(normalize_tag("qwen2.5:7b"), "qwen2.5:7b");
+    }
+}
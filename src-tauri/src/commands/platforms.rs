@@ -0,0 +1,565 @@
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+use super::config::{read_openclaw_config, PlatformEntry};
+use super::logging::{redact, send_request};
+use super::secrets::resolve_value;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlatformFailure {
+    InvalidToken,
+    /// The token works but lacks a permission the bot needs.
+    MissingScope,
+    RateLimited,
+    Unreachable,
+    /// No validator exists for the platform.
+    Unsupported,
+    Unknown,
+}
+
+/// Who the token authenticates as.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BotIdentity {
+    pub id: String,
+    pub name: String,
+    pub username: Option<String>,
+    /// Workspace or team the bot belongs to, where the platform has one.
+    pub team: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlatformTestResult {
+    pub platform: String,
+    pub success: bool,
+    pub identity: Option<BotIdentity>,
+    pub failure: Option<PlatformFailure>,
+    pub message: String,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    Text,
+    /// Stored in the secret store rather than the config file.
+    Secret,
+    /// One of `options`.
+    Select,
+    /// Any of `options`.
+    MultiSelect,
+    /// Free-form values, one per line.
+    List,
+}
+
+/// Makes a field required only while another field has a given value.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldCondition {
+    pub field: &'static str,
+    pub equals: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformField {
+    /// Key in the platform entry.
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: FieldKind,
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_when: Option<FieldCondition>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub options: &'static [&'static str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<&'static str>,
+}
+
+impl PlatformField {
+    const fn new(key: &'static str, label: &'static str, kind: FieldKind) -> Self {
+        Self {
+            key,
+            label,
+            kind,
+            required: false,
+            required_when: None,
+            options: &[],
+            default: None,
+            help: None,
+        }
+    }
+
+    const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    const fn required_when(mut self, field: &'static str, equals: &'static str) -> Self {
+        self.required_when = Some(FieldCondition { field, equals });
+        self
+    }
+
+    const fn options(mut self, options: &'static [&'static str]) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn default(mut self, value: Value) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    const fn help(mut self, help: &'static str) -> Self {
+        self.help = Some(help);
+        self
+    }
+}
+
+/// Describes the settings form for one platform.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformSchema {
+    pub platform: &'static str,
+    pub name: &'static str,
+    pub docs_url: &'static str,
+    /// Whether `test_platform_connection` can check it.
+    pub testable: bool,
+    pub fields: Vec<PlatformField>,
+}
+
+/// Forms for every platform the installer knows. Keys match the fields of
+/// the typed `PlatformEntry` variants.
+pub fn platform_schemas() -> Vec<PlatformSchema> {
+    use FieldKind::*;
+
+    let schemas = vec![
+        PlatformSchema {
+            platform: "whatsapp",
+            name: "WhatsApp",
+            docs_url: "https://faq.whatsapp.com/",
+            testable: false,
+            fields: vec![
+                PlatformField::new("pairing", "Pairing", Select)
+                    .required()
+                    .options(&["qr_code", "phone_code"])
+                    .default(json!("qr_code"))
+                    .help("The gateway shows a QR code or a pairing code on first start"),
+                PlatformField::new("phone_number", "Phone number", Text)
+                    .required_when("pairing", "phone_code")
+                    .help("International format, e.g. +14155550123"),
+                PlatformField::new("allow_from", "Allowed senders", List)
+                    .help("Phone numbers the bot answers; leave empty to answer everyone"),
+            ],
+        },
+        PlatformSchema {
+            platform: "telegram",
+            name: "Telegram",
+            docs_url: "https://core.telegram.org/bots#botfather",
+            testable: true,
+            fields: vec![
+                PlatformField::new("bot_token", "Bot token", Secret)
+                    .required()
+                    .help("From @BotFather, e.g. 123456:ABC-DEF…"),
+                PlatformField::new("allowed_chat_ids", "Allowed chat ids", List)
+                    .help("Leave empty to answer in every chat"),
+            ],
+        },
+        PlatformSchema {
+            platform: "discord",
+            name: "Discord",
+            docs_url: "https://discord.com/developers/applications",
+            testable: true,
+            fields: vec![
+                PlatformField::new("bot_token", "Bot token", Secret).required(),
+                PlatformField::new("intents", "Gateway intents", MultiSelect)
+                    .required()
+                    .options(&[
+                        "guilds",
+                        "guild_members",
+                        "guild_messages",
+                        "direct_messages",
+                        "message_content",
+                    ])
+                    .default(json!(["guilds", "guild_messages", "direct_messages", "message_content"]))
+                    .help("guild_members and message_content must also be enabled in the developer portal"),
+                PlatformField::new("guild_allowlist", "Allowed guild ids", List)
+                    .help("Leave empty to allow every guild the bot is invited to"),
+            ],
+        },
+        PlatformSchema {
+            platform: "slack",
+            name: "Slack",
+            docs_url: "https://api.slack.com/apps",
+            testable: true,
+            fields: vec![
+                PlatformField::new("bot_token", "Bot token", Secret)
+                    .required()
+                    .help("Starts with xoxb-"),
+                PlatformField::new("mode", "Connection mode", Select)
+                    .required()
+                    .options(&["socket", "http"])
+                    .default(json!("socket")),
+                PlatformField::new("app_token", "App-level token", Secret)
+                    .required_when("mode", "socket")
+                    .help("Starts with xapp-, needs the connections:write scope"),
+                PlatformField::new("signing_secret", "Signing secret", Secret).required_when("mode", "http"),
+            ],
+        },
+        PlatformSchema {
+            platform: "wechat",
+            name: "WeChat",
+            docs_url: "https://mp.weixin.qq.com/",
+            testable: false,
+            fields: vec![PlatformField::new("token", "AppID + AppSecret", Secret).required()],
+        },
+    ];
+    debug_assert!(schemas.iter().all(|s| {
+        let key = PlatformEntry::token_field(s.platform);
+        key.is_none_or(|key| s.fields.iter().any(|f| f.key == key))
+    }));
+    schemas
+}
+
+#[tauri::command]
+pub async fn get_platform_schemas() -> Result<Vec<PlatformSchema>, String> {
+    Ok(platform_schemas())
+}
+
+/// Checks a platform token with the cheapest call that reveals the bot.
+pub trait PlatformValidator: Send + Sync {
+    fn default_base_url(&self) -> &'static str;
+
+    fn identity_request(&self, client: &Client, base_url: &str, token: &str) -> RequestBuilder;
+
+    /// Reads the bot identity from the response, or classifies the failure.
+    fn parse_identity(&self, status: StatusCode, body: &Value) -> Result<BotIdentity, (PlatformFailure, String)>;
+}
+
+fn str_at(body: &Value, pointer: &str) -> Option<String> {
+    body.pointer(pointer).and_then(|v| match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// Failure from the HTTP status alone, for APIs that use it.
+fn classify_status(status: StatusCode) -> PlatformFailure {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => PlatformFailure::InvalidToken,
+        StatusCode::TOO_MANY_REQUESTS => PlatformFailure::RateLimited,
+        _ => PlatformFailure::Unknown,
+    }
+}
+
+/// Bot API `getMe`; the token is part of the path.
+pub struct Telegram;
+
+impl PlatformValidator for Telegram {
+    fn default_base_url(&self) -> &'static str {
+        "https://api.telegram.org"
+    }
+
+    fn identity_request(&self, client: &Client, base_url: &str, token: &str) -> RequestBuilder {
+        client.get(format!("{}/bot{}/getMe", base_url, token))
+    }
+
+    fn parse_identity(&self, status: StatusCode, body: &Value) -> Result<BotIdentity, (PlatformFailure, String)> {
+        if body.get("ok") == Some(&Value::Bool(true)) {
+            return Ok(BotIdentity {
+                id: str_at(body, "/result/id").unwrap_or_default(),
+                name: str_at(body, "/result/first_name").unwrap_or_default(),
+                username: str_at(body, "/result/username"),
+                team: None,
+            });
+        }
+        let message = str_at(body, "/description").unwrap_or_else(|| status.to_string());
+        // A malformed token gives 404 rather than 401
+        let failure = match status {
+            StatusCode::NOT_FOUND => PlatformFailure::InvalidToken,
+            _ => classify_status(status),
+        };
+        Err((failure, message))
+    }
+}
+
+/// `GET /users/@me` with a `Bot` authorization header.
+pub struct Discord;
+
+impl PlatformValidator for Discord {
+    fn default_base_url(&self) -> &'static str {
+        "https://discord.com/api/v10"
+    }
+
+    fn identity_request(&self, client: &Client, base_url: &str, token: &str) -> RequestBuilder {
+        client
+            .get(format!("{}/users/@me", base_url))
+            .header("Authorization", format!("Bot {}", token))
+    }
+
+    fn parse_identity(&self, status: StatusCode, body: &Value) -> Result<BotIdentity, (PlatformFailure, String)> {
+        if !status.is_success() {
+            let message = str_at(body, "/message").unwrap_or_else(|| status.to_string());
+            return Err((classify_status(status), message));
+        }
+        if body.get("bot") != Some(&Value::Bool(true)) {
+            return Err((
+                PlatformFailure::InvalidToken,
+                "The token belongs to a user account, not a bot".to_string(),
+            ));
+        }
+        Ok(BotIdentity {
+            id: str_at(body, "/id").unwrap_or_default(),
+            name: str_at(body, "/global_name")
+                .or_else(|| str_at(body, "/username"))
+                .unwrap_or_default(),
+            username: str_at(body, "/username"),
+            team: None,
+        })
+    }
+}
+
+/// `auth.test`, which answers 200 with `ok: false` on failure.
+pub struct Slack;
+
+impl PlatformValidator for Slack {
+    fn default_base_url(&self) -> &'static str {
+        "https://slack.com/api"
+    }
+
+    fn identity_request(&self, client: &Client, base_url: &str, token: &str) -> RequestBuilder {
+        client.post(format!("{}/auth.test", base_url)).bearer_auth(token)
+    }
+
+    fn parse_identity(&self, status: StatusCode, body: &Value) -> Result<BotIdentity, (PlatformFailure, String)> {
+        if body.get("ok") == Some(&Value::Bool(true)) {
+            return Ok(BotIdentity {
+                id: str_at(body, "/bot_id")
+                    .or_else(|| str_at(body, "/user_id"))
+                    .unwrap_or_default(),
+                name: str_at(body, "/user").unwrap_or_default(),
+                username: str_at(body, "/user"),
+                team: str_at(body, "/team"),
+            });
+        }
+        let Some(error) = str_at(body, "/error") else {
+            return Err((classify_status(status), status.to_string()));
+        };
+        let failure = match error.as_str() {
+            "invalid_auth" | "not_authed" | "account_inactive" | "token_revoked" | "token_expired" => {
+                PlatformFailure::InvalidToken
+            }
+            "missing_scope" | "not_allowed_token_type" => PlatformFailure::MissingScope,
+            "ratelimited" => PlatformFailure::RateLimited,
+            _ => classify_status(status),
+        };
+        Err((failure, error))
+    }
+}
+
+/// Validator for a `PlatformEntry::platform()`, if the platform has one.
+pub fn validator_for(platform: &str) -> Option<&'static dyn PlatformValidator> {
+    match platform.to_lowercase().as_str() {
+        "telegram" => Some(&Telegram),
+        "discord" => Some(&Discord),
+        "slack" => Some(&Slack),
+        _ => None,
+    }
+}
+
+async fn run_validator(
+    validator: &dyn PlatformValidator,
+    base_url: &str,
+    token: &str,
+) -> Result<BotIdentity, (PlatformFailure, String)> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| (PlatformFailure::Unknown, format!("HTTP client error: {}", e)))?;
+    let resp = send_request(validator.identity_request(&client, base_url, token))
+        .await
+        .map_err(|e| (PlatformFailure::Unreachable, format!("Connection failed: {}", redact(&e.to_string()))))?;
+    let status = resp.status();
+    let text = resp.text().await.unwrap_or_default();
+    let body: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+    validator.parse_identity(status, &body)
+}
+
+/// Checks a platform token and reports the bot it belongs to. Without a
+/// token, the one configured for `platform` is used. `base_url` replaces
+/// the platform's API root, e.g. to reach a proxy or a mock server.
+#[tauri::command]
+pub async fn test_platform_connection(
+    platform: String,
+    token: Option<String>,
+    base_url: Option<String>,
+) -> Result<PlatformTestResult, String> {
+    let token = match token.filter(|t| !t.trim().is_empty()) {
+        Some(token) => resolve_value(token.trim())?,
+        None => read_openclaw_config()
+            .await?
+            .platforms
+            .into_iter()
+            .find(|p| p.platform().eq_ignore_ascii_case(&platform))
+            .and_then(|p| p.token().map(str::to_string))
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| format!("No token is configured for {}", platform))?,
+    };
+
+    let started = Instant::now();
+    let outcome = match validator_for(&platform) {
+        Some(validator) => {
+            let base_url = base_url
+                .filter(|u| !u.trim().is_empty())
+                .unwrap_or_else(|| validator.default_base_url().to_string());
+            run_validator(validator, base_url.trim_end_matches('/'), &token).await
+        }
+        None => Err((
+            PlatformFailure::Unsupported,
+            format!("Token checks are not available for {}", platform),
+        )),
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    let result = match outcome {
+        Ok(identity) => PlatformTestResult {
+            message: format!("Connected as {}", identity.username.as_deref().unwrap_or(&identity.name)),
+            platform,
+            success: true,
+            identity: Some(identity),
+            failure: None,
+            elapsed_ms,
+        },
+        Err((failure, message)) => PlatformTestResult {
+            platform,
+            success: false,
+            identity: None,
+            failure: Some(failure),
+            message: redact(&message),
+            elapsed_ms,
+        },
+    };
+    log::info!(
+        platform:% = result.platform,
+        success = result.success,
+        failure:? = result.failure,
+        elapsed_ms;
+        "platform connection tested"
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{refused_url, StubResponse, StubServer};
+
+    async fn check(
+        validator: &dyn PlatformValidator,
+        status: u16,
+        body: Value,
+    ) -> (Result<BotIdentity, (PlatformFailure, String)>, StubServer) {
+        let server = StubServer::start(move |_| StubResponse::json(status, body.clone())).await;
+        let result = run_validator(validator, &server.url, "secret-token").await;
+        (result, server)
+    }
+
+    fn failure(result: Result<BotIdentity, (PlatformFailure, String)>) -> PlatformFailure {
+        result.expect_err("validator should fail").0
+    }
+
+    #[tokio::test]
+    async fn telegram_reads_bot_identity() {
+        let body = json!({"ok": true, "result": {"id": 42, "is_bot": true, "first_name": "Claw", "username": "claw_bot"}});
+        let (result, server) = check(&Telegram, 200, body).await;
+        let identity = result.unwrap();
+        assert_eq!(identity.id, "42");
+        assert_eq!(identity.name, "Claw");
+        assert_eq!(identity.username.as_deref(), Some("claw_bot"));
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/botsecret-token/getMe");
+    }
+
+    #[tokio::test]
+    async fn telegram_classifies_failures() {
+        for (status, expected) in [
+            (401, PlatformFailure::InvalidToken),
+            (404, PlatformFailure::InvalidToken),
+            (429, PlatformFailure::RateLimited),
+        ] {
+            let body = json!({"ok": false, "error_code": status, "description": "nope"});
+            let (result, _server) = check(&Telegram, status, body).await;
+            let (failure, message) = result.unwrap_err();
+            assert_eq!(failure, expected, "status {}", status);
+            assert_eq!(message, "nope");
+        }
+    }
+
+    #[tokio::test]
+    async fn discord_reads_bot_identity() {
+        let body = json!({"id": "1001", "username": "claw", "global_name": "Claw", "bot": true});
+        let (result, server) = check(&Discord, 200, body).await;
+        let identity = result.unwrap();
+        assert_eq!(identity.id, "1001");
+        assert_eq!(identity.name, "Claw");
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/users/@me");
+        assert_eq!(request.header("authorization"), Some("Bot secret-token"));
+    }
+
+    #[tokio::test]
+    async fn discord_rejects_user_accounts() {
+        let body = json!({"id": "1001", "username": "someone", "bot": false});
+        let (result, _server) = check(&Discord, 200, body).await;
+        assert_eq!(failure(result), PlatformFailure::InvalidToken);
+    }
+
+    #[tokio::test]
+    async fn discord_classifies_failures() {
+        let (result, _server) = check(&Discord, 401, json!({"message": "401: Unauthorized", "code": 0})).await;
+        assert_eq!(failure(result), PlatformFailure::InvalidToken);
+        let (result, _server) = check(&Discord, 429, json!({"message": "You are being rate limited."})).await;
+        assert_eq!(failure(result), PlatformFailure::RateLimited);
+    }
+
+    #[tokio::test]
+    async fn slack_reads_bot_identity() {
+        let body = json!({"ok": true, "user": "clawbot", "user_id": "U1", "team": "Acme", "bot_id": "B1"});
+        let (result, server) = check(&Slack, 200, body).await;
+        let identity = result.unwrap();
+        assert_eq!(identity.id, "B1");
+        assert_eq!(identity.team.as_deref(), Some("Acme"));
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/auth.test");
+        assert_eq!(request.header("authorization"), Some("Bearer secret-token"));
+    }
+
+    #[tokio::test]
+    async fn slack_classifies_errors_in_ok_responses() {
+        for (error, expected) in [
+            ("invalid_auth", PlatformFailure::InvalidToken),
+            ("missing_scope", PlatformFailure::MissingScope),
+            ("ratelimited", PlatformFailure::RateLimited),
+        ] {
+            let (result, _server) = check(&Slack, 200, json!({"ok": false, "error": error})).await;
+            assert_eq!(failure(result), expected, "error {}", error);
+        }
+        let (result, _server) = check(&Slack, 429, json!({})).await;
+        assert_eq!(failure(result), PlatformFailure::RateLimited);
+    }
+
+    #[tokio::test]
+    async fn refused_connection_is_unreachable() {
+        let url = refused_url().await;
+        for validator in [&Telegram as &dyn PlatformValidator, &Discord, &Slack] {
+            let result = run_validator(validator, &url, "secret-token").await;
+            assert_eq!(failure(result), PlatformFailure::Unreachable);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use super::config::{read_openclaw_config, write_openclaw_config};
+use super::config_store::{get_openclaw_dir, write_file_atomic};
+use super::models::{builtin_presets, validate_model_parameters, ModelPreset};
+
+const PRESETS_FILE: &str = "presets.json";
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PresetsFile {
+    #[serde(default)]
+    presets: Vec<ModelPreset>,
+}
+
+fn presets_path() -> Result<PathBuf, String> {
+    Ok(get_openclaw_dir()?.join(PRESETS_FILE))
+}
+
+fn load_custom_presets() -> Result<Vec<ModelPreset>, String> {
+    let path = presets_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read presets: {}", e))?;
+    let file: PresetsFile = serde_json::from_str(&content).map_err(|e| format!("Failed to parse presets: {}", e))?;
+    Ok(file
+        .presets
+        .into_iter()
+        .map(|p| ModelPreset { builtin: false, ..p })
+        .collect())
+}
+
+fn write_custom_presets(presets: Vec<ModelPreset>) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&PresetsFile { presets })
+        .map_err(|e| format!("Failed to serialize presets: {}", e))?;
+    write_file_atomic(&presets_path()?, &json)
+}
+
+/// Preset names are matched ignoring case and surrounding whitespace, so
+/// "Creative" and "creative " are the same preset everywhere.
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+fn is_builtin(name: &str) -> bool {
+    builtin_presets().iter().any(|p| same_name(&p.name, name))
+}
+
+fn find_preset(name: &str) -> Result<ModelPreset, String> {
+    builtin_presets()
+        .into_iter()
+        .chain(load_custom_presets()?)
+        .find(|p| same_name(&p.name, name))
+        .ok_or_else(|| format!("Preset not found: {}", name))
+}
+
+/// Built-in presets followed by the user's own.
+#[tauri::command]
+pub async fn get_model_presets() -> Result<Vec<ModelPreset>, String> {
+    let mut presets = builtin_presets();
+    presets.extend(load_custom_presets()?);
+    Ok(presets)
+}
+
+/// Creates a custom preset, or replaces the one with the same name. The
+/// parameters must be valid for the preset's provider and model.
+#[tauri::command]
+pub async fn save_model_preset(preset: ModelPreset) -> Result<(), String> {
+    let name = preset.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("Preset name must be 1 to {} characters", MAX_NAME_LEN));
+    }
+    if is_builtin(&name) {
+        return Err(format!("\"{}\" is a built-in preset and cannot be changed", name));
+    }
+
+    let validation =
+        validate_model_parameters(preset.parameters.clone(), preset.provider.clone(), preset.model.clone()).await?;
+    if !validation.valid {
+        let messages: Vec<String> = validation.violations.into_iter().map(|v| v.message).collect();
+        return Err(format!("Invalid preset parameters: {}", messages.join("; ")));
+    }
+
+    let preset = ModelPreset {
+        name: name.clone(),
+        builtin: false,
+        ..preset
+    };
+    let mut presets = load_custom_presets()?;
+    match presets.iter_mut().find(|p| same_name(&p.name, &name)) {
+        Some(existing) => *existing = preset,
+        None => presets.push(preset),
+    }
+    write_custom_presets(presets)?;
+    log::info!(preset:% = name; "preset saved");
+    Ok(())
+}
+
+/// Deletes a custom preset. A config that was set from it keeps its
+/// parameters but no longer names the preset.
+#[tauri::command]
+pub async fn delete_model_preset(name: String) -> Result<(), String> {
+    if is_builtin(&name) {
+        return Err(format!("\"{}\" is a built-in preset and cannot be deleted", name));
+    }
+    let mut presets = load_custom_presets()?;
+    let before = presets.len();
+    presets.retain(|p| !same_name(&p.name, &name));
+    if presets.len() == before {
+        return Err(format!("Preset not found: {}", name));
+    }
+    write_custom_presets(presets)?;
+
+    let mut config = read_openclaw_config().await?;
+    if config.model_preset.as_deref().is_some_and(|p| same_name(p, &name)) {
+        config.model_preset = None;
+        write_openclaw_config(config, None).await?;
+    }
+    log::info!(preset:% = name; "preset deleted");
+    Ok(())
+}
+
+/// Writes the preset's parameters into the OpenClaw config. A preset made
+/// for another provider or model is refused, and the parameters are checked
+/// again against the model the config actually uses.
+#[tauri::command]
+pub async fn apply_model_preset(name: String) -> Result<(), String> {
+    let preset = find_preset(&name)?;
+    let mut config = read_openclaw_config().await?;
+
+    for (target, current, what) in [
+        (&preset.provider, &config.model_provider, "provider"),
+        (&preset.model, &config.model_name, "model"),
+    ] {
+        if let Some(target) = target {
+            if current.as_deref() != Some(target.as_str()) {
+                return Err(format!(
+                    "Preset \"{}\" is for {} {}, but the config uses {}",
+                    name,
+                    what,
+                    target,
+                    current.as_deref().unwrap_or("none")
+                ));
+            }
+        }
+    }
+
+    let validation = validate_model_parameters(
+        preset.parameters.clone(),
+        config.model_provider.clone(),
+        config.model_name.clone(),
+    )
+    .await?;
+    if !validation.valid {
+        let messages: Vec<String> = validation.violations.into_iter().map(|v| v.message).collect();
+        return Err(format!("Preset \"{}\" does not fit the configured model: {}", name, messages.join("; ")));
+    }
+
+    config.model_preset = Some(preset.name);
+    config.model_parameters = Some(preset.parameters);
+    write_openclaw_config(config, None).await?;
+    log::info!(preset:% = name; "preset applied");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::OpenClawConfig;
+    use crate::commands::models::ModelParameters;
+    use crate::commands::test_support::TempHome;
+
+    fn preset(name: &str, temperature: f32) -> ModelPreset {
+        ModelPreset {
+            name: name.to_string(),
+            description: String::new(),
+            parameters: ModelParameters {
+                temperature,
+                ..Default::default()
+            },
+            provider: Some("openai".to_string()),
+            model: Some("gpt-4o".to_string()),
+            builtin: false,
+            i18n_key: None,
+        }
+    }
+
+    async fn use_model(provider: &str, model: &str) {
+        let config = OpenClawConfig {
+            model_provider: Some(provider.to_string()),
+            model_name: Some(model.to_string()),
+            ..Default::default()
+        };
+        write_openclaw_config(config, None).await.unwrap();
+    }
+
+    fn custom_names() -> Vec<(String, f32)> {
+        load_custom_presets()
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.name, p.parameters.temperature))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn saving_replaces_a_preset_whatever_the_case() {
+        let _home = TempHome::new();
+        save_model_preset(preset("Drafting", 0.4)).await.unwrap();
+        save_model_preset(preset(" drafting ", 0.6)).await.unwrap();
+        assert_eq!(custom_names(), [("drafting".to_string(), 0.6)]);
+
+        let err = save_model_preset(preset("creative", 0.5)).await.unwrap_err();
+        assert!(err.contains("built-in"), "{}", err);
+        let err = save_model_preset(preset("Hot", 5.0)).await.unwrap_err();
+        assert!(err.starts_with("Invalid preset parameters"), "{}", err);
+        assert_eq!(custom_names().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn applying_checks_the_configured_model() {
+        let _home = TempHome::new();
+        save_model_preset(preset("Drafting", 0.4)).await.unwrap();
+
+        use_model("deepseek", "deepseek-chat").await;
+        let err = apply_model_preset("Drafting".to_string()).await.unwrap_err();
+        assert!(err.contains("is for provider openai"), "{}", err);
+
+        use_model("openai", "gpt-4o").await;
+        apply_model_preset("DRAFTING".to_string()).await.unwrap();
+        let config = read_openclaw_config().await.unwrap();
+        assert_eq!(config.model_preset.as_deref(), Some("Drafting"));
+        assert_eq!(config.model_parameters.unwrap().temperature, 0.4);
+
+        let err = apply_model_preset("missing".to_string()).await.unwrap_err();
+        assert_eq!(err, "Preset not found: missing");
+    }
+
+    #[tokio::test]
+    async fn deleting_the_applied_preset_clears_it_from_the_config() {
+        let _home = TempHome::new();
+        use_model("openai", "gpt-4o").await;
+        save_model_preset(preset("Drafting", 0.4)).await.unwrap();
+        save_model_preset(preset("Review", 0.2)).await.unwrap();
+        apply_model_preset("Drafting".to_string()).await.unwrap();
+
+        delete_model_preset("Review".to_string()).await.unwrap();
+        assert_eq!(read_openclaw_config().await.unwrap().model_preset.as_deref(), Some("Drafting"));
+
+        delete_model_preset("drafting".to_string()).await.unwrap();
+        assert!(custom_names().is_empty());
+        let config = read_openclaw_config().await.unwrap();
+        assert_eq!(config.model_preset, None);
+        assert_eq!(config.model_parameters.unwrap().temperature, 0.4);
+
+        let err = delete_model_preset("Balanced".to_string()).await.unwrap_err();
+        assert!(err.contains("built-in"), "{}", err);
+        let err = delete_model_preset("Drafting".to_string()).await.unwrap_err();
+        assert_eq!(err, "Preset not found: Drafting");
+    }
+}
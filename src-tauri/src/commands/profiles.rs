@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use super::config::{read_openclaw_config, write_openclaw_config, OpenClawConfig};
+use super::config_store::{get_openclaw_dir, write_file_atomic};
+use super::migrations::{migrate_document, CURRENT_SCHEMA_VERSION};
+use super::secrets::{externalize_profile_secrets, prune_secrets, resolve_secrets};
+use super::service::gateway_restart;
+
+const PROFILES_DIR: &str = "profiles";
+/// Holds the name of the profile that was last activated.
+const ACTIVE_MARKER: &str = ".active";
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProfileFile {
+    name: String,
+    updated_at: String,
+    config: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub updated_at: String,
+    pub model_provider: Option<String>,
+    pub model_name: Option<String>,
+    pub active: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileActivation {
+    pub name: String,
+    pub gateway_restarted: bool,
+}
+
+fn profiles_dir() -> Result<PathBuf, String> {
+    Ok(get_openclaw_dir()?.join(PROFILES_DIR))
+}
+
+/// Profile names become file names, so only a safe subset is accepted.
+fn profile_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid profile name \"{}\": use up to {} letters, digits, '-' or '_'",
+            name, MAX_NAME_LEN
+        ));
+    }
+    Ok(profiles_dir()?.join(format!("{}.json", name)))
+}
+
+fn read_profile(name: &str) -> Result<(ProfileFile, OpenClawConfig), String> {
+    let path = profile_path(name)?;
+    if !path.exists() {
+        return Err(format!("Profile not found: {}", name));
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read profile: {}", e))?;
+    let mut file: ProfileFile =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse profile: {}", e))?;
+    migrate_document(&mut file.config)?;
+    let config = serde_json::from_value(file.config.clone())
+        .map_err(|e| format!("Failed to parse profile: {}", e))?;
+    Ok((file, config))
+}
+
+fn active_profile() -> Option<String> {
+    let marker = profiles_dir().ok()?.join(ACTIVE_MARKER);
+    fs::read_to_string(marker)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+#[tauri::command]
+pub async fn list_profiles() -> Result<Vec<ProfileSummary>, String> {
+    let dir = profiles_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let active = active_profile();
+
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read profiles directory: {}", e))?
+        .flatten()
+        .filter_map(|e| {
+            let path = e.path();
+            match path.extension().and_then(|x| x.to_str()) {
+                Some("json") => path.file_stem().map(|s| s.to_string_lossy().to_string()),
+                _ => None,
+            }
+        })
+        .collect();
+    names.sort();
+
+    let mut profiles = Vec::new();
+    for name in names {
+        match read_profile(&name) {
+            Ok((file, config)) => profiles.push(ProfileSummary {
+                active: active.as_deref() == Some(name.as_str()),
+                name,
+                updated_at: file.updated_at,
+                model_provider: config.model_provider,
+                model_name: config.model_name,
+            }),
+            Err(e) => log::warn!(profile:% = name, error:% = e; "skipping unreadable profile"),
+        }
+    }
+    Ok(profiles)
+}
+
+/// Saves `config` under `name`, or a snapshot of the active config when no
+/// config is given. An existing profile with the same name is replaced.
+#[tauri::command]
+pub async fn save_profile(name: String, config: Option<OpenClawConfig>) -> Result<(), String> {
+    let path = profile_path(&name)?;
+    let mut config = match config {
+        Some(config) => config,
+        None => read_openclaw_config().await?,
+    };
+    config.schema_version = CURRENT_SCHEMA_VERSION;
+    externalize_profile_secrets(&mut config, &name)?;
+    let previous = read_profile(&name).ok().map(|(_, previous)| previous);
+
+    let file = ProfileFile {
+        name: name.clone(),
+        updated_at: chrono::Local::now().to_rfc3339(),
+        config: serde_json::to_value(&config)
+            .map_err(|e| format!("Failed to serialize profile: {}", e))?,
+    };
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    write_file_atomic(&path, &json)?;
+    // Only once the new profile is on disk: a failed write keeps the old one usable
+    if let Some(previous) = previous {
+        prune_secrets(&previous, &config)?;
+    }
+    log::info!(profile:% = name; "profile saved");
+    Ok(())
+}
+
+/// Makes the profile the active `openclaw.json` and, when `restart_gateway`
+/// is set, restarts the gateway so it picks the change up.
+#[tauri::command]
+pub async fn activate_profile(
+    name: String,
+    restart_gateway: Option<bool>,
+) -> Result<ProfileActivation, String> {
+    let (_, mut config) = read_profile(&name)?;
+    resolve_secrets(&mut config)?;
+    write_openclaw_config(config, None).await?;
+    write_file_atomic(&profiles_dir()?.join(ACTIVE_MARKER), &name)?;
+    log::info!(profile:% = name; "profile activated");
+
+    let gateway_restarted = restart_gateway.unwrap_or(false);
+    if gateway_restarted {
+        gateway_restart()
+            .await
+            .map_err(|e| format!("Profile activated but gateway restart failed: {}", e))?;
+    }
+
+    Ok(ProfileActivation {
+        name,
+        gateway_restarted,
+    })
+}
+
+#[tauri::command]
+pub async fn delete_profile(name: String) -> Result<(), String> {
+    let (_, config) = read_profile(&name)?;
+    fs::remove_file(profile_path(&name)?).map_err(|e| format!("Failed to delete profile: {}", e))?;
+    prune_secrets(&config, &OpenClawConfig::default())?;
+
+    if active_profile().as_deref() == Some(name.as_str()) {
+        let _ = fs::remove_file(profiles_dir()?.join(ACTIVE_MARKER));
+    }
+    log::info!(profile:% = name; "profile deleted");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::secrets::{is_secret_ref, load_secret};
+    use crate::commands::test_support::TempHome;
+
+    fn profile_config(key: &str) -> OpenClawConfig {
+        serde_json::from_value(serde_json::json!({
+            "model_provider": "openai",
+            "model_name": "gpt-4o",
+            "api_key": key,
+            "platforms": [{"platform": "telegram", "bot_token": "111:bot"}]
+        }))
+        .unwrap()
+    }
+
+    fn stored_key(name: &str) -> String {
+        let (_, config) = read_profile(name).unwrap();
+        let key = config.api_key.unwrap();
+        assert!(is_secret_ref(&key), "{}", key);
+        key.trim_start_matches("secret:").to_string()
+    }
+
+    #[tokio::test]
+    async fn profiles_round_trip_through_save_activate_and_delete() {
+        let home = TempHome::new();
+        save_profile("work".into(), Some(profile_config("sk-work-one"))).await.unwrap();
+        let first = stored_key("work");
+        let raw = fs::read_to_string(profile_path("work").unwrap()).unwrap();
+        assert!(!raw.contains("sk-work-one") && !raw.contains("111:bot"), "{}", raw);
+
+        // Re-saving with a rotated key drops the old secret
+        save_profile("work".into(), Some(profile_config("sk-work-two"))).await.unwrap();
+        let second = stored_key("work");
+        assert_ne!(first, second);
+        assert_eq!(load_secret(&first).unwrap(), None);
+        assert_eq!(load_secret(&second).unwrap().as_deref(), Some("sk-work-two"));
+
+        let activation = activate_profile("work".into(), None).await.unwrap();
+        assert!(!activation.gateway_restarted);
+        let active = read_openclaw_config().await.unwrap();
+        assert_eq!(active.api_key.as_deref(), Some("sk-work-two"));
+        let platforms = serde_json::to_value(&active.platforms).unwrap();
+        assert_eq!(platforms[0]["bot_token"], "111:bot");
+        let listed = list_profiles().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].active);
+
+        delete_profile("work".into()).await.unwrap();
+        assert!(!profile_path("work").unwrap().exists());
+        assert!(!home.path().join(PROFILES_DIR).join(ACTIVE_MARKER).exists());
+        assert_eq!(load_secret(&second).unwrap(), None);
+        // The active config keeps its own copy of the key
+        let active = read_openclaw_config().await.unwrap();
+        assert_eq!(active.api_key.as_deref(), Some("sk-work-two"));
+    }
+
+    #[tokio::test]
+    async fn failed_delete_keeps_the_profile_secrets() {
+        let _home = TempHome::new();
+        save_profile("home".into(), Some(profile_config("sk-home"))).await.unwrap();
+        let key = stored_key("home");
+
+        // A directory in place of the file makes `remove_file` fail
+        let path = profile_path("home").unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("keep"), &saved).unwrap();
+        assert!(delete_profile("home".into()).await.is_err());
+        assert_eq!(load_secret(&key).unwrap().as_deref(), Some("sk-home"));
+    }
+}
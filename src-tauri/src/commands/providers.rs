@@ -0,0 +1,665 @@
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Map, Value};
+
+use super::catalog::load_providers;
+use super::models::{ApiStyle, AuthStyle, ModelParameters, ModelProvider};
+
+/// Anthropic API version sent with every request.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// What one event of a streamed chat response carries. Token counts, when
+/// present, are totals so far rather than increments.
+#[derive(Debug, Default)]
+pub struct StreamDelta {
+    pub text: Option<String>,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    pub finish_reason: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Knows how a provider's API is called: where a minimal request goes, how it
+/// is authenticated, and where the error message sits in a failure body.
+pub trait ProviderAdapter: Send + Sync {
+    /// How the default requests send the API key.
+    fn auth(&self) -> AuthStyle {
+        AuthStyle::Bearer
+    }
+
+    /// Endpoint used when the user leaves it empty.
+    fn default_endpoint(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The cheapest request that proves endpoint, key and model all work.
+    fn test_request(&self, client: &Client, endpoint: &str, api_key: &str, model: &str) -> RequestBuilder;
+
+    /// Request that lists the models the key can use, if the provider has one.
+    fn models_request(&self, client: &Client, endpoint: &str, api_key: &str) -> Option<RequestBuilder> {
+        Some(authorize(client.get(format!("{}/models", endpoint)), self.auth(), api_key))
+    }
+
+    /// Model ids from a successful `models_request` response.
+    fn parse_models(&self, body: &Value) -> Vec<String> {
+        ids_at(body, "data", "id")
+    }
+
+    /// Streaming chat request answered with server-sent events, if the
+    /// provider has one.
+    fn chat_request(
+        &self,
+        client: &Client,
+        endpoint: &str,
+        api_key: &str,
+        model: &str,
+        prompt: &str,
+        params: &ModelParameters,
+    ) -> Option<RequestBuilder> {
+        Some(
+            authorize(client.post(format!("{}/chat/completions", endpoint)), self.auth(), api_key)
+                .json(&openai_chat_body(model, prompt, params)),
+        )
+    }
+
+    /// Reads the JSON `data` of one server-sent event.
+    fn parse_stream_event(&self, data: &Value) -> StreamDelta {
+        let choice = data.pointer("/choices/0");
+        StreamDelta {
+            text: choice
+                .and_then(|c| c.pointer("/delta/content"))
+                .and_then(|t| t.as_str())
+                .map(str::to_string),
+            prompt_tokens: data.pointer("/usage/prompt_tokens").and_then(|n| n.as_u64()),
+            completion_tokens: data.pointer("/usage/completion_tokens").and_then(|n| n.as_u64()),
+            finish_reason: choice
+                .and_then(|c| c.get("finish_reason"))
+                .and_then(|r| r.as_str())
+                .map(str::to_string),
+            error: data.pointer("/error/message").and_then(|m| m.as_str()).map(str::to_string),
+        }
+    }
+
+    /// Human-readable message from an error response body.
+    fn parse_error(&self, body: &str) -> Option<String> {
+        let value: Value = serde_json::from_str(body).ok()?;
+        value
+            .pointer("/error/message")
+            .or_else(|| value.get("message"))
+            .or_else(|| value.get("error"))
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+    }
+}
+
+/// Adds the API key the way `auth` says.
+fn authorize(request: RequestBuilder, auth: AuthStyle, api_key: &str) -> RequestBuilder {
+    match auth {
+        AuthStyle::Bearer => request.bearer_auth(api_key),
+        AuthStyle::XApiKey => request.header("x-api-key", api_key),
+        AuthStyle::XGoogApiKey => request.header("x-goog-api-key", api_key),
+        AuthStyle::None => request,
+    }
+}
+
+/// `/chat/completions`. Most providers speak this dialect, usually with a
+/// Bearer key.
+pub struct OpenAiCompatible {
+    pub auth: AuthStyle,
+}
+
+impl ProviderAdapter for OpenAiCompatible {
+    fn auth(&self) -> AuthStyle {
+        self.auth
+    }
+
+    fn test_request(&self, client: &Client, endpoint: &str, api_key: &str, model: &str) -> RequestBuilder {
+        authorize(client.post(format!("{}/chat/completions", endpoint)), self.auth, api_key)
+            .json(&json!({
+                "model": model,
+                "messages": [{"role": "user", "content": "hi"}],
+                "max_tokens": 5
+            }))
+    }
+}
+
+/// Chat completion body. Neutral sampling values are left out, since some
+/// models reject parameters they do not support even at their defaults.
+fn openai_chat_body(model: &str, prompt: &str, params: &ModelParameters) -> Value {
+    let mut body = json!({
+        "model": model,
+        "messages": [{"role": "user", "content": prompt}],
+        "stream": true,
+        "stream_options": {"include_usage": true},
+        "temperature": params.temperature,
+        "max_tokens": params.max_tokens,
+    });
+    let extra = body.as_object_mut().expect("body is an object");
+    insert_unless(extra, "top_p", params.top_p, 1.0);
+    insert_unless(extra, "frequency_penalty", params.frequency_penalty, 0.0);
+    insert_unless(extra, "presence_penalty", params.presence_penalty, 0.0);
+    body
+}
+
+fn insert_unless(body: &mut Map<String, Value>, key: &str, value: f32, neutral: f32) {
+    if value != neutral {
+        body.insert(key.to_string(), json!(value));
+    }
+}
+
+pub struct Anthropic;
+
+impl ProviderAdapter for Anthropic {
+    fn default_endpoint(&self) -> Option<&'static str> {
+        Some("https://api.anthropic.com/v1")
+    }
+
+    fn test_request(&self, client: &Client, endpoint: &str, api_key: &str, model: &str) -> RequestBuilder {
+        // Accept the endpoint with or without the version segment
+        let base = endpoint.strip_suffix("/v1").unwrap_or(endpoint);
+        client
+            .post(format!("{}/v1/messages", base))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&json!({
+                "model": model,
+                "max_tokens": 5,
+                "messages": [{"role": "user", "content": "hi"}]
+            }))
+    }
+
+    fn models_request(&self, client: &Client, endpoint: &str, api_key: &str) -> Option<RequestBuilder> {
+        let base = endpoint.strip_suffix("/v1").unwrap_or(endpoint);
+        Some(
+            client
+                .get(format!("{}/v1/models?limit=1000", base))
+                .header("x-api-key", api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION),
+        )
+    }
+
+    fn chat_request(
+        &self,
+        client: &Client,
+        endpoint: &str,
+        api_key: &str,
+        model: &str,
+        prompt: &str,
+        params: &ModelParameters,
+    ) -> Option<RequestBuilder> {
+        let base = endpoint.strip_suffix("/v1").unwrap_or(endpoint);
+        let mut body = json!({
+            "model": model,
+            "max_tokens": params.max_tokens,
+            "messages": [{"role": "user", "content": prompt}],
+            "stream": true,
+            "temperature": params.temperature,
+        });
+        insert_unless(body.as_object_mut().expect("body is an object"), "top_p", params.top_p, 1.0);
+        Some(
+            client
+                .post(format!("{}/v1/messages", base))
+                .header("x-api-key", api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&body),
+        )
+    }
+
+    fn parse_stream_event(&self, data: &Value) -> StreamDelta {
+        let mut delta = StreamDelta::default();
+        match data.get("type").and_then(|t| t.as_str()) {
+            Some("message_start") => {
+                delta.prompt_tokens = data.pointer("/message/usage/input_tokens").and_then(|n| n.as_u64());
+            }
+            Some("content_block_delta") => {
+                delta.text = data.pointer("/delta/text").and_then(|t| t.as_str()).map(str::to_string);
+            }
+            Some("message_delta") => {
+                delta.completion_tokens = data.pointer("/usage/output_tokens").and_then(|n| n.as_u64());
+                delta.finish_reason = data
+                    .pointer("/delta/stop_reason")
+                    .and_then(|r| r.as_str())
+                    .map(str::to_string);
+            }
+            Some("error") => {
+                delta.error = data.pointer("/error/message").and_then(|m| m.as_str()).map(str::to_string);
+            }
+            _ => {}
+        }
+        delta
+    }
+}
+
+/// Native Gemini API, keyed with `x-goog-api-key`.
+pub struct Gemini;
+
+impl ProviderAdapter for Gemini {
+    fn default_endpoint(&self) -> Option<&'static str> {
+        Some("https://generativelanguage.googleapis.com/v1beta")
+    }
+
+    fn test_request(&self, client: &Client, endpoint: &str, api_key: &str, model: &str) -> RequestBuilder {
+        // The wizard offers the OpenAI-compatible path; the native API sits above it
+        let base = endpoint.strip_suffix("/openai").unwrap_or(endpoint);
+        let model = model.strip_prefix("models/").unwrap_or(model);
+        client
+            .post(format!("{}/models/{}:generateContent", base, model))
+            .header("x-goog-api-key", api_key)
+            .json(&json!({
+                "contents": [{"parts": [{"text": "hi"}]}],
+                "generationConfig": {"maxOutputTokens": 5}
+            }))
+    }
+
+    fn models_request(&self, client: &Client, endpoint: &str, api_key: &str) -> Option<RequestBuilder> {
+        let base = endpoint.strip_suffix("/openai").unwrap_or(endpoint);
+        Some(
+            client
+                .get(format!("{}/models?pageSize=1000", base))
+                .header("x-goog-api-key", api_key),
+        )
+    }
+
+    /// Only models that can chat, without the `models/` prefix.
+    fn parse_models(&self, body: &Value) -> Vec<String> {
+        body.get("models")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|m| {
+                m.get("supportedGenerationMethods")
+                    .and_then(|g| g.as_array())
+                    .is_some_and(|g| g.iter().any(|x| x == "generateContent"))
+            })
+            .filter_map(|m| m.get("name").and_then(|n| n.as_str()))
+            .map(|n| n.strip_prefix("models/").unwrap_or(n).to_string())
+            .collect()
+    }
+
+    fn chat_request(
+        &self,
+        client: &Client,
+        endpoint: &str,
+        api_key: &str,
+        model: &str,
+        prompt: &str,
+        params: &ModelParameters,
+    ) -> Option<RequestBuilder> {
+        let base = endpoint.strip_suffix("/openai").unwrap_or(endpoint);
+        let model = model.strip_prefix("models/").unwrap_or(model);
+        let mut config = json!({
+            "temperature": params.temperature,
+            "maxOutputTokens": params.max_tokens,
+        });
+        let config_map = config.as_object_mut().expect("config is an object");
+        insert_unless(config_map, "topP", params.top_p, 1.0);
+        insert_unless(config_map, "frequencyPenalty", params.frequency_penalty, 0.0);
+        insert_unless(config_map, "presencePenalty", params.presence_penalty, 0.0);
+        Some(
+            client
+                .post(format!("{}/models/{}:streamGenerateContent?alt=sse", base, model))
+                .header("x-goog-api-key", api_key)
+                .json(&json!({
+                    "contents": [{"role": "user", "parts": [{"text": prompt}]}],
+                    "generationConfig": config
+                })),
+        )
+    }
+
+    fn parse_stream_event(&self, data: &Value) -> StreamDelta {
+        let candidate = data.pointer("/candidates/0");
+        let text: String = candidate
+            .and_then(|c| c.pointer("/content/parts"))
+            .and_then(|p| p.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+            .collect();
+        StreamDelta {
+            text: (!text.is_empty()).then_some(text),
+            prompt_tokens: data.pointer("/usageMetadata/promptTokenCount").and_then(|n| n.as_u64()),
+            completion_tokens: data.pointer("/usageMetadata/candidatesTokenCount").and_then(|n| n.as_u64()),
+            finish_reason: candidate
+                .and_then(|c| c.get("finishReason"))
+                .and_then(|r| r.as_str())
+                .map(str::to_string),
+            error: data.pointer("/error/message").and_then(|m| m.as_str()).map(str::to_string),
+        }
+    }
+}
+
+/// Bedrock Converse API with a Bedrock API key as Bearer token.
+pub struct Bedrock;
+
+impl ProviderAdapter for Bedrock {
+    fn default_endpoint(&self) -> Option<&'static str> {
+        Some("https://bedrock-runtime.us-east-1.amazonaws.com")
+    }
+
+    fn test_request(&self, client: &Client, endpoint: &str, api_key: &str, model: &str) -> RequestBuilder {
+        // Model ids such as `anthropic.claude-3-haiku-20240307-v1:0` contain ':'
+        let model = url_escape(model);
+        client
+            .post(format!("{}/model/{}/converse", endpoint, model))
+            .bearer_auth(api_key)
+            .json(&json!({
+                "messages": [{"role": "user", "content": [{"text": "hi"}]}],
+                "inferenceConfig": {"maxTokens": 5}
+            }))
+    }
+
+    /// Model listing lives on the control-plane API, which API keys cannot call.
+    fn models_request(&self, _client: &Client, _endpoint: &str, _api_key: &str) -> Option<RequestBuilder> {
+        None
+    }
+
+    /// ConverseStream answers in the binary AWS event-stream format, not SSE.
+    fn chat_request(
+        &self,
+        _client: &Client,
+        _endpoint: &str,
+        _api_key: &str,
+        _model: &str,
+        _prompt: &str,
+        _params: &ModelParameters,
+    ) -> Option<RequestBuilder> {
+        None
+    }
+
+    fn parse_error(&self, body: &str) -> Option<String> {
+        let value: Value = serde_json::from_str(body).ok()?;
+        value
+            .get("message")
+            .or_else(|| value.get("Message"))
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+    }
+}
+
+pub struct Ollama;
+
+impl ProviderAdapter for Ollama {
+    fn default_endpoint(&self) -> Option<&'static str> {
+        Some("http://localhost:11434")
+    }
+
+    fn test_request(&self, client: &Client, endpoint: &str, _api_key: &str, _model: &str) -> RequestBuilder {
+        client.get(format!("{}/api/tags", endpoint))
+    }
+
+    fn models_request(&self, client: &Client, endpoint: &str, _api_key: &str) -> Option<RequestBuilder> {
+        Some(client.get(format!("{}/api/tags", endpoint)))
+    }
+
+    fn parse_models(&self, body: &Value) -> Vec<String> {
+        ids_at(body, "models", "name")
+    }
+
+    /// The OpenAI-compatible API streams SSE; the native `/api/chat` does not.
+    fn chat_request(
+        &self,
+        client: &Client,
+        endpoint: &str,
+        _api_key: &str,
+        model: &str,
+        prompt: &str,
+        params: &ModelParameters,
+    ) -> Option<RequestBuilder> {
+        Some(
+            client
+                .post(format!("{}/v1/chat/completions", endpoint))
+                .json(&openai_chat_body(model, prompt, params)),
+        )
+    }
+}
+
+/// `body[list][*][field]` as strings, the shape most listing endpoints use.
+fn ids_at(body: &Value, list: &str, field: &str) -> Vec<String> {
+    body.get(list)
+        .and_then(|l| l.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.get(field).and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .collect()
+}
+
+fn url_escape(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn openai_compatible(auth: AuthStyle) -> &'static dyn ProviderAdapter {
+    static BEARER: OpenAiCompatible = OpenAiCompatible { auth: AuthStyle::Bearer };
+    static X_API_KEY: OpenAiCompatible = OpenAiCompatible { auth: AuthStyle::XApiKey };
+    static X_GOOG_API_KEY: OpenAiCompatible = OpenAiCompatible { auth: AuthStyle::XGoogApiKey };
+    static NONE: OpenAiCompatible = OpenAiCompatible { auth: AuthStyle::None };
+    match auth {
+        AuthStyle::Bearer => &BEARER,
+        AuthStyle::XApiKey => &X_API_KEY,
+        AuthStyle::XGoogApiKey => &X_GOOG_API_KEY,
+        AuthStyle::None => &NONE,
+    }
+}
+
+/// Adapter for a catalog or custom provider, from its API style and auth.
+pub fn adapter_for_provider(provider: &ModelProvider) -> &'static dyn ProviderAdapter {
+    match provider.api {
+        ApiStyle::OpenAi => openai_compatible(provider.auth),
+        ApiStyle::Anthropic => &Anthropic,
+        ApiStyle::Gemini => &Gemini,
+        ApiStyle::Bedrock => &Bedrock,
+        ApiStyle::Ollama => &Ollama,
+    }
+}
+
+/// Adapter for a `ModelProvider.id`, as the catalog describes it. Ids the
+/// catalog does not know are guessed from the id, and otherwise treated as
+/// OpenAI-compatible, which covers most hosted and self-hosted gateways.
+pub fn adapter_for(provider_id: &str) -> &'static dyn ProviderAdapter {
+    if let Some(provider) = load_providers().iter().find(|p| p.id == provider_id) {
+        return adapter_for_provider(provider);
+    }
+    match provider_id {
+        "anthropic" => &Anthropic,
+        "google" | "gemini" => &Gemini,
+        "bedrock" => &Bedrock,
+        "ollama" => &Ollama,
+        _ => openai_compatible(AuthStyle::Bearer),
+    }
+}
+
+/// Resolves the endpoint a request should go to, without a trailing slash.
+pub fn resolve_endpoint(adapter: &dyn ProviderAdapter, endpoint: &str) -> Result<String, String> {
+    let endpoint = endpoint.trim();
+    let endpoint = if endpoint.is_empty() {
+        adapter.default_endpoint().ok_or("API endpoint is required")?
+    } else {
+        endpoint
+    };
+    Ok(endpoint.trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{RecordedRequest, StubResponse, StubServer};
+
+    /// Sends `request` to a stub answering `status` with `body`, and returns
+    /// what the stub received and the response text.
+    async fn exchange(
+        request: impl FnOnce(&Client, &str) -> RequestBuilder,
+        status: u16,
+        body: Value,
+    ) -> (RecordedRequest, u16, String) {
+        let server = StubServer::start(move |_| StubResponse::json(status, body.clone())).await;
+        let client = Client::new();
+        let response = request(&client, &server.url).send().await.expect("stub answers");
+        let code = response.status().as_u16();
+        let text = response.text().await.unwrap();
+        (server.requests().remove(0), code, text)
+    }
+
+    #[tokio::test]
+    async fn openai_compatible_uses_bearer_and_reads_models() {
+        let adapter = openai_compatible(AuthStyle::Bearer);
+        let (request, _, text) = exchange(
+            |c, url| adapter.models_request(c, url, "sk-test").unwrap(),
+            200,
+            json!({"data": [{"id": "gpt-4o"}, {"id": "gpt-4o-mini"}]}),
+        )
+        .await;
+        assert_eq!(request.path, "/models");
+        assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+        let models = adapter.parse_models(&serde_json::from_str(&text).unwrap());
+        assert_eq!(models, vec!["gpt-4o", "gpt-4o-mini"]);
+
+        let (request, code, text) = exchange(
+            |c, url| adapter.test_request(c, url, "sk-test", "gpt-4o"),
+            401,
+            json!({"error": {"message": "Incorrect API key provided"}}),
+        )
+        .await;
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/chat/completions"));
+        assert_eq!(code, 401);
+        assert_eq!(adapter.parse_error(&text).as_deref(), Some("Incorrect API key provided"));
+    }
+
+    #[tokio::test]
+    async fn openai_compatible_follows_catalog_auth() {
+        let provider = ModelProvider {
+            id: "custom-gateway".to_string(),
+            name: "Gateway".to_string(),
+            models: Vec::new(),
+            default_model: String::new(),
+            supports_streaming: true,
+            max_tokens: 4096,
+            endpoint: None,
+            api: ApiStyle::OpenAi,
+            auth: AuthStyle::XApiKey,
+            docs_url: None,
+            model_details: Vec::new(),
+            custom: true,
+        };
+        let adapter = adapter_for_provider(&provider);
+        let (request, _, _) = exchange(|c, url| adapter.test_request(c, url, "key-1", "m"), 200, json!({})).await;
+        assert_eq!(request.header("x-api-key"), Some("key-1"));
+        assert_eq!(request.header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn anthropic_uses_x_api_key() {
+        let adapter: &dyn ProviderAdapter = &Anthropic;
+        let (request, _, text) = exchange(
+            |c, url| adapter.models_request(c, &format!("{}/v1", url), "sk-ant").unwrap(),
+            200,
+            json!({"data": [{"id": "claude-sonnet-4-20250514"}]}),
+        )
+        .await;
+        assert_eq!(request.path, "/v1/models?limit=1000");
+        assert_eq!(request.header("x-api-key"), Some("sk-ant"));
+        assert_eq!(request.header("anthropic-version"), Some(ANTHROPIC_VERSION));
+        assert_eq!(
+            adapter.parse_models(&serde_json::from_str(&text).unwrap()),
+            vec!["claude-sonnet-4-20250514"]
+        );
+
+        let (request, _, text) = exchange(
+            |c, url| adapter.test_request(c, url, "sk-ant", "claude"),
+            401,
+            json!({"type": "error", "error": {"type": "authentication_error", "message": "invalid x-api-key"}}),
+        )
+        .await;
+        assert_eq!(request.path, "/v1/messages");
+        assert_eq!(adapter.parse_error(&text).as_deref(), Some("invalid x-api-key"));
+    }
+
+    #[tokio::test]
+    async fn gemini_uses_goog_key_and_native_paths() {
+        let adapter: &dyn ProviderAdapter = &Gemini;
+        let (request, _, text) = exchange(
+            |c, url| adapter.models_request(c, &format!("{}/v1beta/openai", url), "AIza").unwrap(),
+            200,
+            json!({"models": [
+                {"name": "models/gemini-2.5-flash", "supportedGenerationMethods": ["generateContent"]},
+                {"name": "models/text-embedding-004", "supportedGenerationMethods": ["embedContent"]}
+            ]}),
+        )
+        .await;
+        assert_eq!(request.path, "/v1beta/models?pageSize=1000");
+        assert_eq!(request.header("x-goog-api-key"), Some("AIza"));
+        assert_eq!(adapter.parse_models(&serde_json::from_str(&text).unwrap()), vec!["gemini-2.5-flash"]);
+
+        let (request, _, text) = exchange(
+            |c, url| adapter.test_request(c, url, "AIza", "models/gemini-2.5-flash"),
+            400,
+            json!({"error": {"code": 400, "message": "API key not valid", "status": "INVALID_ARGUMENT"}}),
+        )
+        .await;
+        assert_eq!(request.path, "/models/gemini-2.5-flash:generateContent");
+        assert_eq!(adapter.parse_error(&text).as_deref(), Some("API key not valid"));
+    }
+
+    #[tokio::test]
+    async fn bedrock_escapes_model_and_reads_errors() {
+        let adapter: &dyn ProviderAdapter = &Bedrock;
+        assert!(adapter.models_request(&Client::new(), "http://unused", "k").is_none());
+        let (request, _, text) = exchange(
+            |c, url| adapter.test_request(c, url, "bedrock-key", "anthropic.claude-3-haiku-20240307-v1:0"),
+            403,
+            json!({"Message": "The security token included in the request is invalid."}),
+        )
+        .await;
+        assert_eq!(request.path, "/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse");
+        assert_eq!(request.header("authorization"), Some("Bearer bedrock-key"));
+        assert_eq!(
+            adapter.parse_error(&text).as_deref(),
+            Some("The security token included in the request is invalid.")
+        );
+    }
+
+    #[tokio::test]
+    async fn ollama_lists_tags_without_auth() {
+        let adapter: &dyn ProviderAdapter = &Ollama;
+        let (request, _, text) = exchange(
+            |c, url| adapter.test_request(c, url, "", "llama3"),
+            200,
+            json!({"models": [{"name": "llama3:latest"}, {"name": "qwen2.5:7b"}]}),
+        )
+        .await;
+        assert_eq!(request.path, "/api/tags");
+        assert_eq!(request.header("authorization"), None);
+        assert_eq!(
+            adapter.parse_models(&serde_json::from_str(&text).unwrap()),
+            vec!["llama3:latest", "qwen2.5:7b"]
+        );
+
+        let (_, code, text) = exchange(
+            |c, url| adapter.models_request(c, url, "").unwrap(),
+            500,
+            json!({"error": "model runner has unexpectedly stopped"}),
+        )
+        .await;
+        assert_eq!(code, 500);
+        assert_eq!(adapter.parse_error(&text).as_deref(), Some("model runner has unexpectedly stopped"));
+    }
+
+    #[tokio::test]
+    async fn catalog_chooses_adapter_dialect() {
+        let (request, _, _) =
+            exchange(|c, url| adapter_for("anthropic").test_request(c, url, "k", "m"), 200, json!({})).await;
+        assert_eq!(request.path, "/v1/messages");
+        assert_eq!(request.header("x-api-key"), Some("k"));
+
+        let (request, _, _) =
+            exchange(|c, url| adapter_for("deepseek").test_request(c, url, "k", "m"), 200, json!({})).await;
+        assert_eq!(request.path, "/chat/completions");
+        assert_eq!(request.header("authorization"), Some("Bearer k"));
+
+        assert!(adapter_for("bedrock").models_request(&Client::new(), "http://unused", "k").is_none());
+    }
+}
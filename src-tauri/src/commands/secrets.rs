@@ -0,0 +1,513 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use super::config::OpenClawConfig;
+use super::backup::{backup_secret_names, scrub_backups};
+use super::config_store::{get_openclaw_dir, load_stored_config, render_config, ConfigWrite};
+use super::migrations::CURRENT_SCHEMA_VERSION;
+
+const KEYRING_SERVICE: &str = "openclaw-installer";
+const SECRETS_FILE: &str = "secrets.enc";
+const SECRETS_KEY_FILE: &str = ".secrets.key";
+const NONCE_LEN: usize = 12;
+
+/// Config values starting with this prefix name a secret instead of holding it.
+pub const SECRET_REF_PREFIX: &str = "secret:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    Keyring,
+    EncryptedFile,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecretMigrationResult {
+    pub backend: SecretBackend,
+    pub migrated: Vec<String>,
+}
+
+/// Picks the OS keyring when it is usable, otherwise (Linux without a secret
+/// service) the encrypted file under `~/.openclaw`. Probed once per process.
+fn backend() -> SecretBackend {
+    // Tests keep secrets in their temp OpenClaw dir, away from the real keyring
+    if cfg!(test) {
+        return SecretBackend::EncryptedFile;
+    }
+    static BACKEND: OnceLock<SecretBackend> = OnceLock::new();
+    *BACKEND.get_or_init(|| {
+        let probe = keyring::Entry::new(KEYRING_SERVICE, "__probe__").and_then(|e| e.get_password());
+        match probe {
+            Ok(_) | Err(keyring::Error::NoEntry) => SecretBackend::Keyring,
+            Err(e) if cfg!(target_os = "linux") => {
+                log::warn!(error:% = e; "secret service unavailable, using encrypted file");
+                SecretBackend::EncryptedFile
+            }
+            Err(e) => {
+                log::warn!(error:% = e; "keyring probe failed");
+                SecretBackend::Keyring
+            }
+        }
+    })
+}
+
+pub fn is_secret_ref(value: &str) -> bool {
+    value.starts_with(SECRET_REF_PREFIX)
+}
+
+pub fn secret_ref(name: &str) -> String {
+    format!("{}{}", SECRET_REF_PREFIX, name)
+}
+
+pub fn store_secret(name: &str, value: &str) -> Result<(), String> {
+    match backend() {
+        SecretBackend::Keyring => keyring::Entry::new(KEYRING_SERVICE, name)
+            .and_then(|e| e.set_password(value))
+            .map_err(|e| format!("Failed to store secret {}: {}", name, e)),
+        SecretBackend::EncryptedFile => {
+            let mut secrets = read_secrets_file()?;
+            secrets.insert(name.to_string(), value.to_string());
+            write_secrets_file(&secrets)
+        }
+    }
+}
+
+pub fn load_secret(name: &str) -> Result<Option<String>, String> {
+    match backend() {
+        SecretBackend::Keyring => {
+            match keyring::Entry::new(KEYRING_SERVICE, name).and_then(|e| e.get_password()) {
+                Ok(value) => Ok(Some(value)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(format!("Failed to read secret {}: {}", name, e)),
+            }
+        }
+        SecretBackend::EncryptedFile => Ok(read_secrets_file()?.remove(name)),
+    }
+}
+
+pub fn delete_secret(name: &str) -> Result<(), String> {
+    match backend() {
+        SecretBackend::Keyring => {
+            match keyring::Entry::new(KEYRING_SERVICE, name).and_then(|e| e.delete_credential()) {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(format!("Failed to delete secret {}: {}", name, e)),
+            }
+        }
+        SecretBackend::EncryptedFile => {
+            let mut secrets = read_secrets_file()?;
+            if secrets.remove(name).is_some() {
+                write_secrets_file(&secrets)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Resolves a config value that may be a secret reference. Plaintext values
+/// pass through unchanged.
+pub fn resolve_value(value: &str) -> Result<String, String> {
+    match value.strip_prefix(SECRET_REF_PREFIX) {
+        Some(name) => Ok(load_secret(name)?.unwrap_or_else(|| {
+            log::warn!(secret:% = name; "referenced secret is missing from the store");
+            String::new()
+        })),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Moves a plaintext value into the store and returns the reference that
+/// replaces it. References and empty values are returned unchanged.
+pub fn externalize_value(name: &str, value: &str) -> Result<String, String> {
+    if value.is_empty() || is_secret_ref(value) {
+        return Ok(value.to_string());
+    }
+    store_secret(name, value)?;
+    Ok(secret_ref(name))
+}
+
+/// Per-install salt for `secret_name`, kept in the store itself.
+const NAME_SALT: &str = "name-salt";
+
+fn name_salt() -> Result<String, String> {
+    use aes_gcm::aead::rand_core::RngCore;
+
+    if let Some(salt) = load_secret(NAME_SALT)? {
+        return Ok(salt);
+    }
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let salt = BASE64.encode(bytes);
+    store_secret(NAME_SALT, &salt)?;
+    Ok(salt)
+}
+
+/// Names a secret after what it is plus a salted hash of its value. Two
+/// Telegram bots, or two hops for the same model, never share a name; saving
+/// an unchanged value reuses its name; a rotated key gets a new one, so
+/// backups keep pointing at the key they were taken with.
+fn secret_name(salt: &str, base: &str, value: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::new()
+        .chain_update(salt)
+        .chain_update(base)
+        .chain_update([0])
+        .chain_update(value)
+        .finalize();
+    format!("{}.{}", base, &format!("{:x}", digest)[..16])
+}
+
+/// Replaces every secret in `config` with a reference, storing the plaintext.
+/// Returns the names of the secrets that were moved.
+pub fn externalize_secrets(config: &mut OpenClawConfig) -> Result<Vec<String>, String> {
+    externalize_secrets_as(config, "")
+}
+
+/// Like `externalize_secrets`, but stores the secrets of a saved profile under
+/// its own names so they never collide with the active config's.
+pub fn externalize_profile_secrets(
+    config: &mut OpenClawConfig,
+    profile: &str,
+) -> Result<Vec<String>, String> {
+    externalize_secrets_as(config, &format!("profile.{}.", profile))
+}
+
+fn externalize_secrets_as(config: &mut OpenClawConfig, prefix: &str) -> Result<Vec<String>, String> {
+    let mut plaintext: Vec<(String, &mut String)> = Vec::new();
+    if let Some(key) = config.api_key.as_mut() {
+        plaintext.push((format!("{}api_key", prefix), key));
+    }
+    for entry in config.platforms.iter_mut() {
+        let platform = entry.platform().to_string();
+        for (field, value) in entry.secrets_mut() {
+            plaintext.push((format!("{}platform.{}.{}", prefix, platform, field), value));
+        }
+    }
+    for hop in config.model_fallbacks.iter_mut() {
+        if let Some(key) = hop.api_key.as_mut() {
+            plaintext.push((format!("{}fallback.{}.{}.api_key", prefix, hop.provider, hop.model), key));
+        }
+    }
+    plaintext.retain(|(_, value)| !value.is_empty() && !is_secret_ref(value));
+    if plaintext.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let salt = name_salt()?;
+    let mut moved = Vec::new();
+    for (base, value) in plaintext {
+        let name = secret_name(&salt, &base, value);
+        *value = externalize_value(&name, value)?;
+        moved.push(name);
+    }
+    Ok(moved)
+}
+
+/// Every secret field that is set, plaintext or reference, in a fixed order.
+fn secret_values(config: &OpenClawConfig) -> Vec<&String> {
+    config
+        .api_key
+        .iter()
+        .chain(config.platforms.iter().flat_map(|p| p.secrets().into_iter().map(|(_, v)| v)))
+        .chain(config.model_fallbacks.iter().filter_map(|h| h.api_key.as_ref()))
+        .collect()
+}
+
+fn secret_refs(config: &OpenClawConfig) -> Vec<&str> {
+    secret_values(config)
+        .into_iter()
+        .filter_map(|v| v.strip_prefix(SECRET_REF_PREFIX))
+        .collect()
+}
+
+/// Names of the secrets referenced anywhere in a config document.
+pub fn secret_names(value: &serde_json::Value) -> BTreeSet<String> {
+    fn walk(value: &serde_json::Value, out: &mut BTreeSet<String>) {
+        match value {
+            serde_json::Value::String(s) => {
+                out.extend(s.strip_prefix(SECRET_REF_PREFIX).map(str::to_string));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| walk(v, out)),
+            serde_json::Value::Object(map) => map.values().for_each(|v| walk(v, out)),
+            _ => {}
+        }
+    }
+    let mut names = BTreeSet::new();
+    walk(value, &mut names);
+    names
+}
+
+/// Referenced secrets of a config document that the store does not hold.
+pub fn missing_secrets(value: &serde_json::Value) -> Result<Vec<String>, String> {
+    let mut missing = Vec::new();
+    for name in secret_names(value) {
+        if load_secret(&name)?.is_none() {
+            missing.push(name);
+        }
+    }
+    Ok(missing)
+}
+
+/// Every secret `config` references anywhere, including untyped settings
+/// kept in `extra`.
+fn referenced_secrets(config: &OpenClawConfig) -> Result<BTreeSet<String>, String> {
+    let value = serde_json::to_value(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    Ok(secret_names(&value))
+}
+
+/// Deletes stored secrets that `old` referenced but neither `new` nor any
+/// config backup still does, e.g. the token of a removed platform. Those
+/// kept for a backup go when it is trimmed.
+pub fn prune_secrets(old: &OpenClawConfig, new: &OpenClawConfig) -> Result<(), String> {
+    let mut keep = referenced_secrets(new)?;
+    keep.extend(backup_secret_names()?);
+    for name in secret_refs(old) {
+        if !keep.contains(name) {
+            delete_secret(name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces every secret reference in `config` with the stored value.
+pub fn resolve_secrets(config: &mut OpenClawConfig) -> Result<(), String> {
+    if let Some(key) = config.api_key.as_mut() {
+        *key = resolve_value(key)?;
+    }
+    for entry in config.platforms.iter_mut() {
+        for (_, value) in entry.secrets_mut() {
+            *value = resolve_value(value)?;
+        }
+    }
+    for key in config.model_fallbacks.iter_mut().filter_map(|h| h.api_key.as_mut()) {
+        *key = resolve_value(key)?;
+    }
+    Ok(())
+}
+
+fn secrets_path() -> Result<PathBuf, String> {
+    Ok(get_openclaw_dir()?.join(SECRETS_FILE))
+}
+
+/// Loads the file encryption key, creating it with owner-only permissions on
+/// first use.
+fn file_key() -> Result<Key<Aes256Gcm>, String> {
+    let dir = get_openclaw_dir()?;
+    let path = dir.join(SECRETS_KEY_FILE);
+    if let Ok(bytes) = fs::read(&path) {
+        if bytes.len() == 32 {
+            return Ok(*Key::<Aes256Gcm>::from_slice(&bytes));
+        }
+        return Err("Secret key file is corrupt".to_string());
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let key = Aes256Gcm::generate_key(OsRng);
+    write_private(&path, key.as_slice()).map_err(|e| format!("Failed to write secret key: {}", e))?;
+    Ok(key)
+}
+
+/// Writes a file only the owner can read. The mode is set when the file is
+/// created, so there is no moment at which the umask default applies.
+fn write_private(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // `mode` only applies to new files; tighten one an older version wrote
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    // Files under the user profile are already private on Windows
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+fn read_secrets_file() -> Result<BTreeMap<String, String>, String> {
+    let path = secrets_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let encoded = fs::read_to_string(&path).map_err(|e| format!("Failed to read secrets: {}", e))?;
+    let data = BASE64
+        .decode(encoded.trim())
+        .map_err(|e| format!("Secrets file is corrupt: {}", e))?;
+    if data.len() < NONCE_LEN {
+        return Err("Secrets file is corrupt".to_string());
+    }
+
+    let cipher = Aes256Gcm::new(&file_key()?);
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt secrets file".to_string())?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Secrets file is corrupt: {}", e))
+}
+
+fn write_secrets_file(secrets: &BTreeMap<String, String>) -> Result<(), String> {
+    let path = secrets_path()?;
+    let plaintext = serde_json::to_vec(secrets).map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+
+    let cipher = Aes256Gcm::new(&file_key()?);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| "Failed to encrypt secrets".to_string())?;
+
+    let mut data = nonce.to_vec();
+    data.extend_from_slice(&ciphertext);
+    write_private(&path, BASE64.encode(data).as_bytes()).map_err(|e| format!("Failed to write secrets: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_secret_backend() -> Result<SecretBackend, String> {
+    Ok(backend())
+}
+
+/// Moves plaintext secrets in `openclaw.json` into the store. Runs at startup;
+/// the command lets the UI trigger it again.
+pub fn move_plaintext_secrets() -> Result<SecretMigrationResult, String> {
+    let mut write = ConfigWrite::begin(None)?;
+    let Some(mut config) = load_stored_config()? else {
+        return Ok(SecretMigrationResult {
+            backend: backend(),
+            migrated: Vec::new(),
+        });
+    };
+    let plaintext = config.clone();
+    let migrated = externalize_secrets(&mut config)?;
+
+    if !migrated.is_empty() {
+        config.schema_version = CURRENT_SCHEMA_VERSION;
+        write.commit(&render_config(&config)?)?;
+        // Backups, including the one just taken, still hold the plaintext
+        let replacements: Vec<(String, String)> = secret_values(&plaintext)
+            .into_iter()
+            .zip(secret_values(&config))
+            .filter(|(old, new)| old != new)
+            .map(|(old, new)| (old.clone(), new.clone()))
+            .collect();
+        scrub_backups(&replacements)?;
+        log::info!(count = migrated.len(); "plaintext secrets moved to secret store");
+    }
+
+    Ok(SecretMigrationResult {
+        backend: backend(),
+        migrated,
+    })
+}
+
+#[tauri::command]
+pub async fn migrate_secrets() -> Result<SecretMigrationResult, String> {
+    move_plaintext_secrets()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempHome;
+
+    fn two_bots() -> OpenClawConfig {
+        serde_json::from_value(serde_json::json!({
+            "api_key": "sk-first-key",
+            "platforms": [
+                {"platform": "telegram", "bot_token": "111:first"},
+                {"platform": "telegram", "bot_token": "222:second"}
+            ],
+            "model_fallbacks": [
+                {"provider": "openai", "model": "gpt-4o", "api_key": "sk-hop-one"},
+                {"provider": "openai", "model": "gpt-4o", "api_key": "sk-hop-two"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn entries_of_the_same_kind_get_their_own_secret() {
+        let _home = TempHome::new();
+        let mut config = two_bots();
+        let moved = externalize_secrets(&mut config).unwrap();
+        assert_eq!(moved.len(), 5);
+        assert_eq!(moved.iter().collect::<BTreeSet<_>>().len(), 5, "{:?}", moved);
+
+        resolve_secrets(&mut config).unwrap();
+        assert_eq!(config.platforms[0].token(), Some("111:first"));
+        assert_eq!(config.platforms[1].token(), Some("222:second"));
+        assert_eq!(config.model_fallbacks[0].api_key.as_deref(), Some("sk-hop-one"));
+        assert_eq!(config.model_fallbacks[1].api_key.as_deref(), Some("sk-hop-two"));
+    }
+
+    #[test]
+    fn unchanged_values_keep_their_name_and_rotated_ones_do_not() {
+        let _home = TempHome::new();
+        let mut first = two_bots();
+        externalize_secrets(&mut first).unwrap();
+
+        let mut again = two_bots();
+        externalize_secrets(&mut again).unwrap();
+        assert_eq!(secret_refs(&first), secret_refs(&again));
+
+        let mut rotated = two_bots();
+        rotated.api_key = Some("sk-rotated-key".to_string());
+        externalize_secrets(&mut rotated).unwrap();
+        assert_ne!(rotated.api_key, first.api_key);
+        assert_eq!(resolve_value(first.api_key.as_ref().unwrap()).unwrap(), "sk-first-key");
+        assert_eq!(resolve_value(rotated.api_key.as_ref().unwrap()).unwrap(), "sk-rotated-key");
+    }
+
+    #[test]
+    fn references_in_extra_are_kept() {
+        let config: OpenClawConfig = serde_json::from_value(serde_json::json!({
+            "api_key": "secret:api-key",
+            "platforms": [
+                {"platform": "telegram", "bot_token": "secret:platform-telegram-bot-token"},
+                {"platform": "matrix", "token": "plain", "access_token": "secret:matrix-access"}
+            ],
+            "plugins": {"search": {"key": "secret:search-key"}}
+        }))
+        .unwrap();
+
+        let refs = referenced_secrets(&config).unwrap();
+        let expected = ["api-key", "matrix-access", "platform-telegram-bot-token", "search-key"];
+        assert_eq!(refs.iter().map(String::as_str).collect::<Vec<_>>(), expected);
+    }
+
+    #[tokio::test]
+    async fn plaintext_config_is_rewritten_to_references() {
+        let home = TempHome::new();
+        let path = home.path().join("openclaw.json");
+        fs::write(
+            &path,
+            r#"{"model_provider": "openai", "api_key": "sk-plaintext-key", "platforms": [{"platform": "discord", "bot_token": "discord-token"}]}"#,
+        )
+        .unwrap();
+
+        let result = migrate_secrets().await.unwrap();
+        assert_eq!(result.migrated.len(), 2);
+
+        let on_disk = fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("sk-plaintext-key"), "{}", on_disk);
+        assert!(!on_disk.contains("discord-token"), "{}", on_disk);
+        let doc: serde_json::Value = serde_json::from_str(&on_disk).unwrap();
+        assert!(is_secret_ref(doc["api_key"].as_str().unwrap()));
+        assert!(is_secret_ref(doc["platforms"][0]["bot_token"].as_str().unwrap()));
+
+        let mut config = load_stored_config().unwrap().unwrap();
+        resolve_secrets(&mut config).unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("sk-plaintext-key"));
+        assert!(migrate_secrets().await.unwrap().migrated.is_empty());
+    }
+}
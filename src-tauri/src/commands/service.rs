@@ -1,7 +1,8 @@
 use serde::Serialize;
 use std::process::Command;
 
-use super::config::read_openclaw_config;
+use super::effective::resolve_effective_config;
+use super::logging::run_command;
 
 #[derive(Debug, Serialize)]
 pub struct GatewayStatusResult {
@@ -24,9 +25,7 @@ fn find_openclaw() -> String {
 #[tauri::command]
 pub async fn gateway_start() -> Result<String, String> {
     let openclaw = find_openclaw();
-    let output = Command::new(&openclaw)
-        .args(["gateway", "start"])
-        .output()
+    let output = run_command(Command::new(&openclaw).args(["gateway", "start"]))
         .map_err(|e| format!("Failed to start gateway: {}", e))?;
 
     if output.status.success() {
@@ -39,9 +38,7 @@ pub async fn gateway_start() -> Result<String, String> {
 #[tauri::command]
 pub async fn gateway_stop() -> Result<String, String> {
     let openclaw = find_openclaw();
-    let output = Command::new(&openclaw)
-        .args(["gateway", "stop"])
-        .output()
+    let output = run_command(Command::new(&openclaw).args(["gateway", "stop"]))
         .map_err(|e| format!("Failed to stop gateway: {}", e))?;
 
     if output.status.success() {
@@ -54,11 +51,9 @@ pub async fn gateway_stop() -> Result<String, String> {
 #[tauri::command]
 pub async fn gateway_restart() -> Result<String, String> {
     let openclaw = find_openclaw();
-    let _ = Command::new(&openclaw).args(["gateway", "stop"]).output();
+    let _ = run_command(Command::new(&openclaw).args(["gateway", "stop"]));
     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-    let output = Command::new(&openclaw)
-        .args(["gateway", "start"])
-        .output()
+    let output = run_command(Command::new(&openclaw).args(["gateway", "start"]))
         .map_err(|e| format!("Failed to restart gateway: {}", e))?;
 
     if output.status.success() {
@@ -70,7 +65,9 @@ pub async fn gateway_restart() -> Result<String, String> {
 
 #[tauri::command]
 pub async fn gateway_status() -> Result<GatewayStatusResult, String> {
-    let config = read_openclaw_config().await.unwrap_or_default();
+    let config = resolve_effective_config(Default::default())
+        .map(|effective| effective.config)
+        .unwrap_or_default();
     let port = config.gateway_port;
     let running = std::net::TcpStream::connect_timeout(
         &format!("127.0.0.1:{}", port).parse().unwrap(),
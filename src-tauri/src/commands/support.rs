@@ -0,0 +1,316 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tauri::AppHandle;
+
+use super::config::OpenClawConfig;
+use super::config_store::{get_config_path, get_openclaw_dir};
+use super::detect::detect_environment;
+use super::doctor::run_doctor;
+use super::effective::resolve_effective_config;
+use super::install::{docker_dir, find_npm, find_openclaw};
+use super::logging::{self, is_secret_key, redact, redact_json, run_command, REDACTED};
+use super::secrets::resolve_secrets;
+use super::troubleshoot::run_diagnostics;
+
+/// Only the tail of each gateway log is included to keep bundles small.
+const GATEWAY_LOG_TAIL_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct BundleEntry {
+    pub name: String,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BundleManifest {
+    pub created_at: String,
+    pub installer_version: String,
+    pub os: String,
+    pub entries: Vec<BundleEntry>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupportBundleResult {
+    pub path: String,
+    pub manifest: BundleManifest,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolVersion {
+    tool: String,
+    version: Option<String>,
+}
+
+struct BundleBuilder {
+    entries: Vec<(String, Vec<u8>)>,
+    errors: Vec<String>,
+    secrets: Vec<String>,
+}
+
+impl BundleBuilder {
+    fn add_json<T: Serialize>(&mut self, name: &str, value: &T) {
+        match serde_json::to_value(value) {
+            Ok(mut v) => {
+                redact_json(&mut v);
+                let text = serde_json::to_string_pretty(&v).unwrap_or_default();
+                self.add_text(name, &text);
+            }
+            Err(e) => self.errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    fn add_text(&mut self, name: &str, text: &str) {
+        let scrubbed = self.scrub(text);
+        self.entries.push((name.to_string(), scrubbed.into_bytes()));
+    }
+
+    fn add_result<T: Serialize>(&mut self, name: &str, result: Result<T, String>) {
+        match result {
+            Ok(value) => self.add_json(name, &value),
+            Err(e) => self.errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    /// Removes every known secret value verbatim, then applies the generic
+    /// pattern-based redaction line by line.
+    fn scrub(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for secret in &self.secrets {
+            out = out.replace(secret.as_str(), REDACTED);
+        }
+        out.lines().map(redact).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Secret values from `openclaw.json`, so they can be scrubbed from free-form
+/// logs where no key name gives them away. The file only holds `secret:`
+/// references, so they are resolved first; the logs contain the real values.
+fn collect_config_secrets(value: &serde_json::Value, bundle: &mut BundleBuilder) {
+    collect_secret_values(value, &mut bundle.secrets);
+    let Ok(mut config) = serde_json::from_value::<OpenClawConfig>(value.clone()) else {
+        return;
+    };
+    match resolve_secrets(&mut config).and_then(|_| serde_json::to_value(&config).map_err(|e| e.to_string())) {
+        Ok(resolved) => collect_secret_values(&resolved, &mut bundle.secrets),
+        Err(e) => bundle.errors.push(format!("secrets: failed to resolve for scrubbing: {}", e)),
+    }
+}
+
+/// Adds the redacted config and remembers every secret value, including ones
+/// that only come from `OPENCLAW_*` variables or flags, for scrubbing the logs.
+fn add_config(bundle: &mut BundleBuilder) -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if config_path.exists() {
+        match fs::read_to_string(&config_path) {
+            Ok(text) => match serde_json::from_str::<serde_json::Value>(&text) {
+                Ok(value) => {
+                    collect_config_secrets(&value, bundle);
+                    bundle.add_json("config/openclaw.json", &value);
+                }
+                Err(e) => {
+                    bundle.errors.push(format!("config/openclaw.json: invalid JSON: {}", e));
+                    bundle.add_text("config/openclaw.json.txt", &text);
+                }
+            },
+            Err(e) => bundle.errors.push(format!("config/openclaw.json: {}", e)),
+        }
+    }
+
+    match resolve_effective_config(serde_json::Map::new())
+        .and_then(|effective| serde_json::to_value(&effective.config).map_err(|e| e.to_string()))
+    {
+        Ok(effective) => collect_secret_values(&effective, &mut bundle.secrets),
+        Err(e) => bundle.errors.push(format!("secrets: failed to resolve overrides for scrubbing: {}", e)),
+    }
+    Ok(())
+}
+
+fn collect_secret_values(value: &serde_json::Value, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                match v {
+                    serde_json::Value::String(s) if is_secret_key(k) && s.len() >= 6 => {
+                        out.push(s.clone())
+                    }
+                    _ => collect_secret_values(v, out),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|v| collect_secret_values(v, out)),
+        _ => {}
+    }
+}
+
+fn tool_version(tool: &str, program: &str) -> ToolVersion {
+    let version = run_command(Command::new(program).arg("--version"))
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    ToolVersion {
+        tool: tool.to_string(),
+        version,
+    }
+}
+
+fn read_tail(path: &Path, max_bytes: usize) -> std::io::Result<String> {
+    let content = fs::read(path)?;
+    let start = content.len().saturating_sub(max_bytes);
+    Ok(String::from_utf8_lossy(&content[start..]).to_string())
+}
+
+fn collect_gateway_logs(bundle: &mut BundleBuilder) {
+    if let Ok(dir) = get_openclaw_dir() {
+        let logs_dir = dir.join("logs");
+        if let Ok(entries) = fs::read_dir(&logs_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_file() {
+                    continue;
+                }
+                let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                match read_tail(&path, GATEWAY_LOG_TAIL_BYTES) {
+                    Ok(text) => bundle.add_text(&format!("gateway/{}", name), &text),
+                    Err(e) => bundle.errors.push(format!("gateway/{}: {}", name, e)),
+                }
+            }
+        }
+    }
+
+    // Docker installs log to the container instead
+    if let Ok(out) = run_command(Command::new("docker").args(["logs", "--tail", "500", "openclaw"])) {
+        if out.status.success() {
+            let text = format!(
+                "{}\n{}",
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&out.stderr)
+            );
+            bundle.add_text("gateway/docker.log", &text);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn create_support_bundle(
+    app: AppHandle,
+    destination: Option<String>,
+) -> Result<SupportBundleResult, String> {
+    let mut bundle = BundleBuilder {
+        entries: Vec::new(),
+        errors: Vec::new(),
+        secrets: Vec::new(),
+    };
+
+    add_config(&mut bundle)?;
+
+    if let Ok(dir) = docker_dir() {
+        let compose_path = dir.join("docker-compose.yml");
+        if let Ok(text) = fs::read_to_string(&compose_path) {
+            bundle.add_text("config/docker-compose.yml", &text);
+        }
+    }
+
+    bundle.add_result("environment.json", detect_environment().await);
+    bundle.add_result("doctor.json", run_doctor().await);
+    bundle.add_result("diagnostics.json", run_diagnostics().await);
+
+    let versions = vec![
+        tool_version("node", "node"),
+        tool_version("npm", &find_npm()),
+        tool_version("docker", "docker"),
+        tool_version("openclaw", &find_openclaw()),
+    ];
+    bundle.add_json("versions.json", &versions);
+
+    match logging::collect_log_files(&app) {
+        Ok(files) => {
+            for (name, content) in files {
+                let text = String::from_utf8_lossy(&content).to_string();
+                bundle.add_text(&name, &text);
+            }
+        }
+        Err(e) => bundle.errors.push(format!("installer logs: {}", e)),
+    }
+    collect_gateway_logs(&mut bundle);
+
+    let manifest = BundleManifest {
+        created_at: chrono::Local::now().to_rfc3339(),
+        installer_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: sysinfo::System::long_os_version().unwrap_or_default(),
+        entries: bundle
+            .entries
+            .iter()
+            .map(|(name, content)| BundleEntry {
+                name: name.clone(),
+                bytes: content.len(),
+            })
+            .collect(),
+        errors: bundle.errors.clone(),
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    bundle.entries.insert(0, ("manifest.json".to_string(), manifest_json));
+
+    let path = logging::archive_destination(destination, "openclaw-support");
+    logging::write_zip(&path, &bundle.entries)?;
+    log::info!(path:% = path.display(), entries = bundle.entries.len(); "support bundle created");
+
+    Ok(SupportBundleResult {
+        path: path.to_string_lossy().to_string(),
+        manifest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::write_openclaw_config;
+    use crate::commands::config_store::CONFIG_FILE;
+    use crate::commands::test_support::TempHome;
+
+    fn bundle() -> BundleBuilder {
+        BundleBuilder {
+            entries: Vec::new(),
+            errors: Vec::new(),
+            secrets: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn logs_lose_stored_and_overridden_secrets() {
+        let home = TempHome::new();
+        let config: OpenClawConfig = serde_json::from_value(serde_json::json!({
+            "model_provider": "openai",
+            "model_name": "gpt-4o",
+            "api_key": "stored-key-value",
+            "platforms": [{"platform": "telegram", "bot_token": "4242:platform-token"}]
+        }))
+        .unwrap();
+        write_openclaw_config(config, None).await.unwrap();
+        let stored = fs::read_to_string(home.path().join(CONFIG_FILE)).unwrap();
+        assert!(!stored.contains("stored-key-value"), "{}", stored);
+
+        std::env::set_var("OPENCLAW_API_KEY", "env-key-value");
+        let mut bundle = bundle();
+        let added = add_config(&mut bundle);
+        std::env::remove_var("OPENCLAW_API_KEY");
+        added.unwrap();
+
+        bundle.add_text(
+            "logs/gateway.log",
+            "using stored-key-value\nusing env-key-value\ntelegram 4242:platform-token connected",
+        );
+        assert!(bundle.errors.is_empty(), "{:?}", bundle.errors);
+        for (name, content) in &bundle.entries {
+            let text = String::from_utf8_lossy(content);
+            for secret in ["stored-key-value", "env-key-value", "4242:platform-token"] {
+                assert!(!text.contains(secret), "{} leaks {}: {}", name, secret, text);
+            }
+        }
+        let log = String::from_utf8_lossy(&bundle.entries.last().unwrap().1).to_string();
+        assert!(log.contains("telegram") && log.contains(REDACTED), "{}", log);
+    }
+}
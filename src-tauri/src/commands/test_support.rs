@@ -0,0 +1,188 @@
+//! Helpers shared by the unit tests.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::config_store::OPENCLAW_HOME_ENV;
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path and query, e.g. `/api/tags`.
+    pub path: String,
+    /// Names are lowercase.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+pub struct StubResponse {
+    pub status: u16,
+    /// Written one after another with a short pause, so clients see them
+    /// as separate chunks.
+    pub chunks: Vec<String>,
+    pub content_type: &'static str,
+    pub headers: Vec<(String, String)>,
+}
+
+impl StubResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            chunks: vec![body.to_string()],
+            content_type: "application/json",
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn chunked(status: u16, content_type: &'static str, chunks: &[&str]) -> Self {
+        Self {
+            status,
+            chunks: chunks.iter().map(|c| c.to_string()).collect(),
+            content_type,
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+type Handler = dyn Fn(&RecordedRequest) -> StubResponse + Send + Sync;
+
+/// A one-request-per-connection HTTP/1.1 server on a random local port.
+pub struct StubServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StubServer {
+    pub async fn start(handler: impl Fn(&RecordedRequest) -> StubResponse + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind stub server");
+        let url = format!("http://{}", listener.local_addr().expect("stub address"));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    serve(stream, handler.as_ref(), &recorded).await;
+                });
+            }
+        });
+        Self { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Answers one request. It is recorded before the response goes out, so a
+/// test sees it as soon as the client returns.
+async fn serve(mut stream: TcpStream, handler: &Handler, recorded: &Mutex<Vec<RecordedRequest>>) -> Option<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(n, v)| (n.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+    let length = headers
+        .iter()
+        .find(|(n, _)| n == "content-length")
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = buffer[head_end + 4..].to_vec();
+    while body.len() < length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+
+    let request = RecordedRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    };
+    let response = handler(&request);
+    recorded.lock().unwrap().push(request);
+    let extra: String = response.headers.iter().map(|(n, v)| format!("{}: {}\r\n", n, v)).collect();
+    let head = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Type: {}\r\n{}Connection: close\r\n\r\n",
+        response.status, response.content_type, extra
+    );
+    stream.write_all(head.as_bytes()).await.ok()?;
+    for chunk in &response.chunks {
+        stream.write_all(chunk.as_bytes()).await.ok()?;
+        stream.flush().await.ok()?;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    stream.shutdown().await.ok()
+}
+
+/// A local URL nothing listens on.
+pub async fn refused_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("address");
+    drop(listener);
+    format!("http://{}", addr)
+}
+
+/// Points `OPENCLAW_HOME` at a fresh temp dir for as long as it lives. Tests
+/// that touch the OpenClaw dir hold one, which also runs them one at a time.
+pub struct TempHome {
+    pub dir: tempfile::TempDir,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TempHome {
+    pub fn new() -> Self {
+        static HOME_LOCK: Mutex<()> = Mutex::new(());
+        let lock = HOME_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().expect("temp dir");
+        std::env::set_var(OPENCLAW_HOME_ENV, dir.path());
+        Self { dir, _lock: lock }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+impl Drop for TempHome {
+    fn drop(&mut self) {
+        std::env::remove_var(OPENCLAW_HOME_ENV);
+    }
+}
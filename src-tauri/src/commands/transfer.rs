@@ -0,0 +1,480 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+
+use super::config::{read_openclaw_config, write_openclaw_config, OpenClawConfig};
+use super::config_store::{config_revision, diff_json, escape_pointer, mask_change, write_file_atomic, ConfigChange};
+use super::logging::{export_destination, is_secret_key, redact_json, REDACTED};
+use super::migrations::migrate_document;
+use super::validation::{validate_config, ConfigValidationResult};
+
+const EXPORT_FORMAT: &str = "openclaw-config-export";
+const EXPORT_FORMAT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretMode {
+    /// Secret values are blanked; the recipient enters their own.
+    Stripped,
+    /// Secret values travel encrypted under a passphrase.
+    Encrypted,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Overlay the imported values on the current config.
+    #[default]
+    Merge,
+    /// Use the imported config as is.
+    Replace,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportFile {
+    format: String,
+    format_version: u32,
+    exported_at: String,
+    installer_version: String,
+    secret_mode: SecretMode,
+    /// Encrypted map of JSON pointer to secret value, when `secret_mode` is
+    /// `encrypted`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets: Option<EncryptedSecrets>,
+    config: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedSecrets {
+    kdf: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigExportResult {
+    pub path: String,
+    pub secret_mode: SecretMode,
+    pub secret_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigImportResult {
+    pub mode: ImportMode,
+    /// Changes the import makes to the current config. Secret values are masked.
+    pub changes: Vec<ConfigChange>,
+    pub validation: ConfigValidationResult,
+    /// Pointers of secrets the file did not carry and the config does not have.
+    pub missing_secrets: Vec<String>,
+    pub applied: bool,
+    /// Revision of the config the changes were computed against. Pass it back
+    /// as `expected_revision` to apply exactly what was previewed.
+    pub revision: Option<String>,
+}
+
+/// Moves every non-empty secret in `value` into `out`, keyed by JSON pointer,
+/// leaving an empty string behind.
+fn take_secrets(value: &mut Value, path: &str, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                let child = format!("{}/{}", path, escape_pointer(k));
+                match v {
+                    Value::String(s) if is_secret_key(k) => {
+                        if !s.is_empty() {
+                            out.insert(child, std::mem::take(s));
+                        }
+                    }
+                    _ => take_secrets(v, &child, out),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                take_secrets(item, &format!("{}/{}", path, i), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(*Key::<Aes256Gcm>::from_slice(&key))
+}
+
+fn encrypt_secrets(secrets: &BTreeMap<String, String>, passphrase: &str) -> Result<EncryptedSecrets, String> {
+    let salt: [u8; SALT_LEN] = rand_bytes();
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let plaintext =
+        serde_json::to_vec(secrets).map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| "Failed to encrypt secrets".to_string())?;
+
+    Ok(EncryptedSecrets {
+        kdf: "argon2id".to_string(),
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn decrypt_secrets(secrets: &EncryptedSecrets, passphrase: &str) -> Result<BTreeMap<String, String>, String> {
+    if secrets.kdf != "argon2id" {
+        return Err(format!("Unsupported key derivation: {}", secrets.kdf));
+    }
+    let decode = |s: &str| BASE64.decode(s).map_err(|e| format!("Export file is corrupt: {}", e));
+    let salt = decode(&secrets.salt)?;
+    let nonce = decode(&secrets.nonce)?;
+    let ciphertext = decode(&secrets.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err("Export file is corrupt".to_string());
+    }
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| "Wrong passphrase or corrupt export file".to_string())?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Export file is corrupt: {}", e))
+}
+
+fn rand_bytes<const N: usize>() -> [u8; N] {
+    use aes_gcm::aead::rand_core::RngCore;
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Overlays `top` onto `base`. Blank secrets in `top` keep the value from
+/// `base`, so a stripped export never wipes the recipient's own keys.
+fn overlay(base: &mut Map<String, Value>, top: Map<String, Value>) {
+    for (k, v) in top {
+        match (base.get_mut(&k), v) {
+            (Some(Value::Object(b)), Value::Object(t)) => overlay(b, t),
+            (Some(_), Value::String(s)) if s.is_empty() && is_secret_key(&k) => {}
+            (_, v) => {
+                base.insert(k, v);
+            }
+        }
+    }
+}
+
+/// Merges platform entries by platform name; entries only in `current` stay.
+fn merge_platforms(current: &mut Vec<Value>, imported: Vec<Value>) {
+    let name = |v: &Value| v.get("platform").and_then(|p| p.as_str()).map(str::to_string);
+    for entry in imported {
+        let existing = current
+            .iter_mut()
+            .find(|c| name(c).is_some() && name(c) == name(&entry));
+        match (existing, entry) {
+            (Some(Value::Object(c)), Value::Object(e)) => overlay(c, e),
+            (_, entry) => current.push(entry),
+        }
+    }
+}
+
+fn merge_documents(current: &Value, mut imported: Map<String, Value>) -> Value {
+    let mut merged = current.as_object().cloned().unwrap_or_default();
+    if let (Some(Value::Array(cur)), Some(Value::Array(_))) =
+        (merged.get_mut("platforms"), imported.get("platforms"))
+    {
+        if let Some(Value::Array(imp)) = imported.remove("platforms") {
+            merge_platforms(cur, imp);
+        }
+    }
+    overlay(&mut merged, imported);
+    Value::Object(merged)
+}
+
+/// Pointers of secret fields that are present but empty.
+fn blank_secrets(value: &Value, path: &str, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let child = format!("{}/{}", path, escape_pointer(k));
+                match v {
+                    Value::String(s) if is_secret_key(k) && s.is_empty() => out.push(child),
+                    _ => blank_secrets(v, &child, out),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                blank_secrets(item, &format!("{}/{}", path, i), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Writes the current config to a portable file. With a passphrase the
+/// secrets are encrypted into the file, otherwise they are left out.
+#[tauri::command]
+pub async fn export_openclaw_config(
+    destination: Option<String>,
+    passphrase: Option<String>,
+) -> Result<ConfigExportResult, String> {
+    let config = read_openclaw_config().await?;
+    let mut document =
+        serde_json::to_value(&config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let mut secrets = BTreeMap::new();
+    take_secrets(&mut document, "", &mut secrets);
+
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let (secret_mode, encrypted) = match &passphrase {
+        Some(p) => (SecretMode::Encrypted, Some(encrypt_secrets(&secrets, p)?)),
+        None => (SecretMode::Stripped, None),
+    };
+
+    let file = ExportFile {
+        format: EXPORT_FORMAT.to_string(),
+        format_version: EXPORT_FORMAT_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        installer_version: env!("CARGO_PKG_VERSION").to_string(),
+        secret_mode,
+        secrets: encrypted,
+        config: document,
+    };
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize export: {}", e))?;
+
+    let path = export_destination(destination, "openclaw-config", "json");
+    write_file_atomic(&path, &json)?;
+    log::info!(path:% = path.display(), mode:? = secret_mode; "config exported");
+
+    Ok(ConfigExportResult {
+        path: path.to_string_lossy().to_string(),
+        secret_mode,
+        secret_count: secrets.len(),
+    })
+}
+
+/// Reads an export (or a plain `openclaw.json`), validates it and reports the
+/// changes it would make. Unless `dry_run` is set, the result is written when
+/// validation finds no errors other than secrets the user still has to enter.
+/// With `expected_revision` from a dry run, the import is refused if the
+/// config changed since that preview.
+#[tauri::command]
+pub async fn import_openclaw_config(
+    path: String,
+    passphrase: Option<String>,
+    mode: Option<ImportMode>,
+    dry_run: Option<bool>,
+    expected_revision: Option<String>,
+) -> Result<ConfigImportResult, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let raw: Value =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path, e))?;
+
+    let mut imported = if raw.get("format").and_then(|f| f.as_str()) == Some(EXPORT_FORMAT) {
+        let file: ExportFile =
+            serde_json::from_value(raw).map_err(|e| format!("Invalid export file: {}", e))?;
+        if file.format_version > EXPORT_FORMAT_VERSION {
+            return Err(format!(
+                "Export format {} is newer than this installer supports",
+                file.format_version
+            ));
+        }
+        let mut config = file.config;
+        if let Some(encrypted) = &file.secrets {
+            let passphrase = passphrase
+                .filter(|p| !p.is_empty())
+                .ok_or("This export is encrypted; a passphrase is required")?;
+            for (pointer, secret) in decrypt_secrets(encrypted, &passphrase)? {
+                if let Some(slot) = config.pointer_mut(&pointer) {
+                    *slot = Value::String(secret);
+                }
+            }
+        }
+        config
+    } else {
+        raw
+    };
+    migrate_document(&mut imported)?;
+
+    let mode = mode.unwrap_or_default();
+    // Taken before the read: a change in between makes the write fail, not slip through
+    let revision = config_revision()?;
+    let current = serde_json::to_value(read_openclaw_config().await?)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let result = match (mode, imported) {
+        (ImportMode::Merge, Value::Object(map)) => merge_documents(&current, map),
+        (_, doc) => doc,
+    };
+
+    let config: OpenClawConfig =
+        serde_json::from_value(result.clone()).map_err(|e| format!("Invalid config: {}", e))?;
+    let validation = validate_config(&config).await;
+    let mut missing_secrets = Vec::new();
+    blank_secrets(&result, "", &mut missing_secrets);
+    let changes = diff_json(&current, &result).into_iter().map(mask_change).collect();
+
+    // Blank secrets are expected after a stripped export and are filled in later
+    let blocking = validation
+        .errors
+        .iter()
+        .any(|e| !missing_secrets.contains(&e.pointer));
+    let applied = !dry_run.unwrap_or(false) && !blocking;
+    if applied {
+        write_openclaw_config(config, expected_revision.or_else(|| revision.clone())).await?;
+        log::info!(path:% = path, mode:? = mode; "config imported");
+    }
+
+    Ok(ConfigImportResult {
+        mode,
+        changes,
+        validation,
+        missing_secrets,
+        applied,
+        revision,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::migrations::CURRENT_SCHEMA_VERSION;
+    use crate::commands::test_support::TempHome;
+
+    fn config(value: Value) -> OpenClawConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn mine() -> OpenClawConfig {
+        config(serde_json::json!({
+            "gateway_port": 18789,
+            "api_key": "sk-mine",
+            "platforms": [{"platform": "telegram", "bot_token": "111:mine"}]
+        }))
+    }
+
+    /// A stripped export of someone else's config.
+    fn write_stripped_export(home: &TempHome) -> String {
+        let path = home.path().join("import.json");
+        let file = serde_json::json!({
+            "format": EXPORT_FORMAT,
+            "format_version": EXPORT_FORMAT_VERSION,
+            "exported_at": "2026-01-01T00:00:00Z",
+            "installer_version": "0.0.0",
+            "secret_mode": "stripped",
+            "config": {
+                "schema_version": CURRENT_SCHEMA_VERSION,
+                "gateway_port": 18800,
+                "api_key": "",
+                "platforms": [
+                    {"platform": "telegram", "bot_token": ""},
+                    {"platform": "discord", "bot_token": ""}
+                ]
+            }
+        });
+        fs::write(&path, file.to_string()).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn secrets_round_trip_under_the_passphrase_only() {
+        let secrets = BTreeMap::from([
+            ("/api_key".to_string(), "sk-secret".to_string()),
+            ("/platforms/0/bot_token".to_string(), "111:bot".to_string()),
+        ]);
+        let encrypted = encrypt_secrets(&secrets, "correct horse").unwrap();
+        assert!(!encrypted.ciphertext.contains("sk-secret"));
+
+        assert_eq!(decrypt_secrets(&encrypted, "correct horse").unwrap(), secrets);
+        let err = decrypt_secrets(&encrypted, "wrong horse").unwrap_err();
+        assert!(err.contains("Wrong passphrase"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn encrypted_export_restores_secrets_on_import() {
+        let home = TempHome::new();
+        write_openclaw_config(mine(), None).await.unwrap();
+        let dest = home.path().join("export.json").to_string_lossy().to_string();
+        let export = export_openclaw_config(Some(dest.clone()), Some("pass".into()))
+            .await
+            .unwrap();
+        assert_eq!(export.secret_mode, SecretMode::Encrypted);
+        assert_eq!(export.secret_count, 2);
+        let raw = fs::read_to_string(&dest).unwrap();
+        assert!(!raw.contains("sk-mine") && !raw.contains("111:mine"), "{}", raw);
+
+        write_openclaw_config(OpenClawConfig::default(), None).await.unwrap();
+        let err = import_openclaw_config(dest.clone(), Some("nope".into()), None, None, None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("Wrong passphrase"), "{}", err);
+
+        let result =
+            import_openclaw_config(dest, Some("pass".into()), Some(ImportMode::Replace), None, None)
+                .await
+                .unwrap();
+        assert!(result.applied);
+        let imported = read_openclaw_config().await.unwrap();
+        assert_eq!(imported.api_key.as_deref(), Some("sk-mine"));
+    }
+
+    #[tokio::test]
+    async fn merge_keeps_own_secrets_and_replace_reports_them_missing() {
+        let home = TempHome::new();
+        write_openclaw_config(mine(), None).await.unwrap();
+        let path = write_stripped_export(&home);
+
+        let replace =
+            import_openclaw_config(path.clone(), None, Some(ImportMode::Replace), Some(true), None)
+                .await
+                .unwrap();
+        assert!(!replace.applied);
+        assert_eq!(
+            replace.missing_secrets,
+            ["/api_key", "/platforms/0/bot_token", "/platforms/1/bot_token"]
+        );
+
+        let merge = import_openclaw_config(path, None, Some(ImportMode::Merge), None, None)
+            .await
+            .unwrap();
+        assert!(merge.applied);
+        assert_eq!(merge.missing_secrets, ["/platforms/1/bot_token"]);
+        let serialized = serde_json::to_string(&merge.changes).unwrap();
+        assert!(!serialized.contains("sk-mine"), "{}", serialized);
+
+        let merged = serde_json::to_value(read_openclaw_config().await.unwrap()).unwrap();
+        assert_eq!(merged["gateway_port"], 18800);
+        assert_eq!(merged["api_key"], "sk-mine");
+        assert_eq!(merged["platforms"][0]["bot_token"], "111:mine");
+        assert_eq!(merged["platforms"][1]["platform"], "discord");
+    }
+
+    #[tokio::test]
+    async fn import_is_refused_when_the_config_changed_since_the_preview() {
+        let home = TempHome::new();
+        write_openclaw_config(mine(), None).await.unwrap();
+        let path = write_stripped_export(&home);
+
+        let preview = import_openclaw_config(path.clone(), None, None, Some(true), None)
+            .await
+            .unwrap();
+        write_openclaw_config(config(serde_json::json!({"gateway_port": 18900})), None)
+            .await
+            .unwrap();
+
+        let err = import_openclaw_config(path, None, None, None, preview.revision)
+            .await
+            .unwrap_err();
+        assert!(err.contains("changed by another program"), "{}", err);
+        let current = read_openclaw_config().await.unwrap();
+        assert_eq!(current.gateway_port, 18900);
+    }
+}
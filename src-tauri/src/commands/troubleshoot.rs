@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+use super::config::OpenClawConfig;
+use super::config_store::{get_config_path, load_stored_config, ConfigWrite};
+use super::logging::run_command;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum IssueSeverity {
     Critical,
@@ -101,9 +105,7 @@ fn check_port_conflict(port: u16) -> Result<(), String> {
 }
 
 fn check_nodejs() -> Result<(), String> {
-    let output = Command::new("node")
-        .arg("--version")
-        .output()
+    let output = run_command(Command::new("node").arg("--version"))
         .map_err(|_| "Node.js is not installed or not in PATH".to_string())?;
 
     if !output.status.success() {
@@ -119,9 +121,7 @@ fn check_nodejs() -> Result<(), String> {
 }
 
 fn check_openclaw_installed() -> Result<(), String> {
-    let output = Command::new("npm")
-        .args(&["list", "-g", "openclaw"])
-        .output()
+    let output = run_command(Command::new("npm").args(&["list", "-g", "openclaw"]))
         .map_err(|_| "Failed to check OpenClaw installation".to_string())?;
 
     if !output.status.success() {
@@ -131,40 +131,29 @@ fn check_openclaw_installed() -> Result<(), String> {
     Ok(())
 }
 
-fn check_config_file() -> Result<(), String> {
-    let home = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map_err(|_| "Cannot determine home directory".to_string())?;
-
-    let config_path = std::path::Path::new(&home).join(".openclaw").join("config.json");
+pub(crate) fn check_config_file() -> Result<(), String> {
+    let config_path = get_config_path()?;
 
     if !config_path.exists() {
         return Err("Configuration file not found".to_string());
     }
 
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config file: {}", e))?;
-
-    serde_json::from_str::<serde_json::Value>(&content)
-        .map_err(|e| format!("Invalid JSON in config file: {}", e))?;
-
-    Ok(())
+    load_stored_config().map(|_| ())
 }
 
 async fn fix_port_conflict(_port: u16) -> Result<String, String> {
     #[cfg(target_os = "windows")]
     {
-        let output = Command::new("netstat")
-            .args(&["-ano", "|", "findstr", &format!(":{}", port)])
-            .output()
+        let output = run_command(
+            Command::new("netstat")
+                .args(&["-ano", "|", "findstr", &format!(":{}", port)]),
+        )
             .map_err(|e| format!("Failed to find process: {}", e))?;
 
         let output_str = String::from_utf8_lossy(&output.stdout);
         if let Some(line) = output_str.lines().next() {
             if let Some(pid) = line.split_whitespace().last() {
-                Command::new("taskkill")
-                    .args(&["/F", "/PID", pid])
-                    .output()
+                run_command(Command::new("taskkill").args(&["/F", "/PID", pid]))
                     .map_err(|e| format!("Failed to kill process: {}", e))?;
 
                 return Ok(format!("Killed process {} using port {}", pid, port));
@@ -175,30 +164,21 @@ async fn fix_port_conflict(_port: u16) -> Result<String, String> {
     Err("Could not automatically fix port conflict".to_string())
 }
 
-async fn fix_config_file() -> Result<String, String> {
-    let home = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map_err(|_| "Cannot determine home directory".to_string())?;
-
-    let config_path = std::path::Path::new(&home).join(".openclaw").join("config.json");
-
-    // Backup existing config
-    if config_path.exists() {
-        let backup_path = config_path.with_extension("json.bak");
-        std::fs::copy(&config_path, &backup_path)
-            .map_err(|e| format!("Failed to backup config: {}", e))?;
-    }
-
-    // Write default config
-    let default_config = serde_json::json!({
-        "gateway_port": 18789,
-        "model_provider": "alibaba",
-        "model_name": "qwen-plus",
-        "api_key": "",
-    });
-
-    std::fs::write(&config_path, serde_json::to_string_pretty(&default_config).unwrap())
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+pub(crate) async fn fix_config_file() -> Result<String, String> {
+    let mut write = ConfigWrite::begin(None)?;
+    // Write default config; the previous file is kept in the backup history
+    let mut default_config = OpenClawConfig {
+        model_provider: Some("alibaba".to_string()),
+        model_name: Some("qwen-plus".to_string()),
+        ..Default::default()
+    };
+    default_config
+        .extra
+        .insert("agent".to_string(), serde_json::json!({}));
+
+    let json = serde_json::to_string_pretty(&default_config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    write.commit(&json)?;
 
     Ok("Configuration reset to defaults".to_string())
 }
@@ -0,0 +1,567 @@
+//! Model usage statistics. The gateway logs one line per model call to
+//! `~/.openclaw/logs`; new lines are folded into `usage.db` on every query so
+//! history survives log rotation.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::catalog::load_providers;
+use super::config_store::get_openclaw_dir;
+use super::models::ModelProvider;
+
+const DATABASE_FILE: &str = "usage.db";
+const LOGS_DIR: &str = "logs";
+/// Older rows are dropped on ingest.
+const RETENTION_DAYS: i64 = 90;
+/// How long a query waits for another ingest holding the database.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The budget monitor and the usage and cost commands all ingest before they
+/// query; one ingest runs at a time.
+static INGEST_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageWindow {
+    Hour,
+    #[default]
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl UsageWindow {
+    /// Start of the window as Unix milliseconds.
+    pub fn since(self) -> i64 {
+        let span = match self {
+            UsageWindow::Hour => ChronoDuration::hours(1),
+            UsageWindow::Day => ChronoDuration::days(1),
+            UsageWindow::Week => ChronoDuration::weeks(1),
+            UsageWindow::Month => ChronoDuration::days(30),
+            UsageWindow::All => return 0,
+        };
+        (Utc::now() - span).timestamp_millis()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelUsageStats {
+    pub provider: String,
+    pub model: String,
+    pub total_requests: u64,
+    pub failed_requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub avg_response_time_ms: u64,
+    pub p50_response_time_ms: Option<u64>,
+    pub p95_response_time_ms: Option<u64>,
+    pub p99_response_time_ms: Option<u64>,
+    pub success_rate: f32,
+    pub error_rate: f32,
+}
+
+/// One model call as the gateway logged it.
+struct UsageRecord {
+    timestamp: i64,
+    provider: Option<String>,
+    model: String,
+    prompt_tokens: Option<u64>,
+    completion_tokens: Option<u64>,
+    latency_ms: Option<u64>,
+    success: bool,
+}
+
+fn open_database() -> Result<Connection, String> {
+    let dir = get_openclaw_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let conn = Connection::open(dir.join(DATABASE_FILE)).map_err(|e| format!("Failed to open usage database: {}", e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Failed to open usage database: {}", e))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS requests (
+            id INTEGER PRIMARY KEY,
+            ts INTEGER NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            prompt_tokens INTEGER,
+            completion_tokens INTEGER,
+            latency_ms INTEGER,
+            success INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS requests_ts ON requests (ts);
+        CREATE TABLE IF NOT EXISTS log_files (
+            file_id TEXT PRIMARY KEY,
+            offset INTEGER NOT NULL
+        );",
+    )
+    .map_err(|e| format!("Failed to initialize usage database: {}", e))?;
+    Ok(conn)
+}
+
+fn first<'a>(value: &'a Value, pointers: &[&str]) -> Option<&'a Value> {
+    pointers.iter().find_map(|p| value.pointer(p)).filter(|v| !v.is_null())
+}
+
+fn first_u64(value: &Value, pointers: &[&str]) -> Option<u64> {
+    first(value, pointers).and_then(|v| v.as_u64().or_else(|| v.as_f64().map(|f| f as u64)))
+}
+
+fn first_str(value: &Value, pointers: &[&str]) -> Option<String> {
+    first(value, pointers).and_then(|v| v.as_str()).map(str::to_string)
+}
+
+/// RFC 3339 strings, or Unix seconds or milliseconds.
+fn parse_timestamp(value: &Value) -> Option<i64> {
+    if let Some(s) = value.as_str() {
+        return DateTime::parse_from_rfc3339(s).ok().map(|t| t.timestamp_millis());
+    }
+    let n = value.as_i64()?;
+    Some(if n > 10_000_000_000 { n } else { n * 1000 })
+}
+
+/// Reads a JSON log line. Lines without a model, or with neither token
+/// counts nor a duration, are not model calls and are skipped. Lines without
+/// a timestamp get `default_timestamp`, or are skipped when there is none.
+fn parse_line(line: &str, default_timestamp: Option<i64>) -> Option<UsageRecord> {
+    let value: Value = serde_json::from_str(line.trim()).ok()?;
+    let model = first_str(&value, &["/model", "/llm/model", "/request/model"])?;
+    let prompt_tokens = first_u64(
+        &value,
+        &["/usage/prompt_tokens", "/usage/input_tokens", "/usage/input", "/prompt_tokens", "/input_tokens"],
+    );
+    let completion_tokens = first_u64(
+        &value,
+        &["/usage/completion_tokens", "/usage/output_tokens", "/usage/output", "/completion_tokens", "/output_tokens"],
+    );
+    let latency_ms = first_u64(&value, &["/latency_ms", "/duration_ms", "/durationMs", "/elapsed_ms"]);
+    if prompt_tokens.is_none() && completion_tokens.is_none() && latency_ms.is_none() {
+        return None;
+    }
+
+    let failed = first(&value, &["/error"]).is_some_and(|e| e != &Value::Bool(false))
+        || first_u64(&value, &["/status", "/status_code"]).is_some_and(|s| s >= 400)
+        || first(&value, &["/success", "/ok"]).and_then(|v| v.as_bool()) == Some(false);
+    let timestamp = first(&value, &["/time", "/timestamp", "/ts"])
+        .and_then(parse_timestamp)
+        .or(default_timestamp)?;
+
+    Some(UsageRecord {
+        timestamp,
+        provider: first_str(&value, &["/provider", "/llm/provider", "/request/provider"]),
+        model,
+        prompt_tokens,
+        completion_tokens,
+        latency_ms,
+        success: !failed,
+    })
+}
+
+/// The catalog provider offering `model`, for lines that omit the provider.
+fn infer_provider(catalog: &[ModelProvider], model: &str) -> String {
+    catalog
+        .iter()
+        .find(|p| p.models.iter().any(|m| m == model))
+        .map(|p| p.id.clone())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Identifies a log file across renames, so a rotated file keeps its offset
+/// and a new file at the same path is read from the start. The hash of the
+/// first line tells apart files that reuse an inode, or elsewhere files
+/// created in the same instant. `None` until the file holds a complete first
+/// line.
+fn file_identity(file: &mut File) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut first_line = Vec::new();
+    BufReader::new(&mut *file).read_until(b'\n', &mut first_line).ok()?;
+    if !first_line.ends_with(b"\n") {
+        return None;
+    }
+    let hash = format!("{:x}", Sha256::digest(&first_line));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let meta = file.metadata().ok()?;
+        Some(format!("{}:{}:{}", meta.dev(), meta.ino(), hash))
+    }
+    #[cfg(not(unix))]
+    {
+        // Creation time survives a rename. A new file at the same path can
+        // inherit it (NTFS tunneling), which the first-line hash then covers
+        let created = file
+            .metadata()
+            .and_then(|m| m.created())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        Some(format!("{}:{}", created, hash))
+    }
+}
+
+/// Last write to the file, as Unix milliseconds. Lines appended since the
+/// previous ingest were written at most this long ago.
+fn modified_millis(file: &File) -> Option<i64> {
+    let modified = file.metadata().and_then(|m| m.modified()).ok()?;
+    Some(DateTime::<Utc>::from(modified).timestamp_millis())
+}
+
+/// Reads the lines appended to the file since the last ingest. A file shorter
+/// than the stored offset was truncated and is read from the start.
+fn ingest_file(conn: &mut Connection, path: &Path) -> Result<usize, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let Some(key) = file_identity(&mut file) else {
+        return Ok(0);
+    };
+
+    // The offset is read and advanced in one write transaction, so two
+    // ingests, even from another process, cannot both read the same lines
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("Failed to update usage database: {}", e))?;
+    let stored: i64 = tx
+        .query_row("SELECT offset FROM log_files WHERE file_id = ?1", params![key], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("Failed to read usage database: {}", e))?
+        .unwrap_or(0);
+
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let start = if (stored as u64) > len { 0 } else { stored as u64 };
+    if start == len {
+        return Ok(0);
+    }
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    // Leave a partially written last line for the next ingest
+    let Some(end) = bytes.iter().rposition(|&b| b == b'\n').map(|i| i + 1) else {
+        return Ok(0);
+    };
+    let text = String::from_utf8_lossy(&bytes[..end]);
+    let modified = modified_millis(&file);
+    let records: Vec<UsageRecord> = text.lines().filter_map(|l| parse_line(l, modified)).collect();
+
+    let catalog = load_providers();
+    for record in &records {
+        let provider = record.provider.clone().unwrap_or_else(|| infer_provider(&catalog, &record.model));
+        tx.execute(
+            "INSERT INTO requests (ts, provider, model, prompt_tokens, completion_tokens, latency_ms, success)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                record.timestamp,
+                provider,
+                record.model,
+                record.prompt_tokens.map(|n| n as i64),
+                record.completion_tokens.map(|n| n as i64),
+                record.latency_ms.map(|n| n as i64),
+                record.success,
+            ],
+        )
+        .map_err(|e| format!("Failed to update usage database: {}", e))?;
+    }
+    tx.execute(
+        "INSERT INTO log_files (file_id, offset) VALUES (?1, ?2)
+         ON CONFLICT(file_id) DO UPDATE SET offset = excluded.offset",
+        params![key, (start + end as u64) as i64],
+    )
+    .map_err(|e| format!("Failed to update usage database: {}", e))?;
+    tx.commit().map_err(|e| format!("Failed to update usage database: {}", e))?;
+    Ok(records.len())
+}
+
+fn ingest_gateway_logs(conn: &mut Connection) -> Result<(), String> {
+    let _guard = INGEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let logs_dir = get_openclaw_dir()?.join(LOGS_DIR);
+    let Ok(entries) = fs::read_dir(&logs_dir) else {
+        return Ok(());
+    };
+    let mut ingested = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        match ingest_file(conn, &path) {
+            Ok(n) => ingested += n,
+            Err(e) => log::warn!(path:% = path.display(), error:% = e; "failed to ingest gateway log"),
+        }
+    }
+
+    let cutoff = (Utc::now() - ChronoDuration::days(RETENTION_DAYS)).timestamp_millis();
+    conn.execute("DELETE FROM requests WHERE ts < ?1", params![cutoff])
+        .map_err(|e| format!("Failed to update usage database: {}", e))?;
+    if ingested > 0 {
+        log::info!(records = ingested; "gateway usage ingested");
+    }
+    Ok(())
+}
+
+/// Nearest-rank percentile of an ascending list.
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn aggregate(conn: &Connection, since: i64) -> Result<Vec<ModelUsageStats>, String> {
+    let db_err = |e: rusqlite::Error| format!("Failed to query usage database: {}", e);
+    let mut groups = conn
+        .prepare(
+            "SELECT provider, model, COUNT(*), SUM(success = 0),
+                    COALESCE(SUM(prompt_tokens), 0), COALESCE(SUM(completion_tokens), 0),
+                    COALESCE(AVG(latency_ms), 0)
+             FROM requests WHERE ts >= ?1
+             GROUP BY provider, model
+             ORDER BY COUNT(*) DESC",
+        )
+        .map_err(db_err)?;
+    let mut latencies = conn
+        .prepare(
+            "SELECT latency_ms FROM requests
+             WHERE ts >= ?1 AND provider = ?2 AND model = ?3 AND latency_ms IS NOT NULL
+             ORDER BY latency_ms",
+        )
+        .map_err(db_err)?;
+
+    let rows = groups
+        .query_map(params![since], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)? as u64,
+                row.get::<_, i64>(3)? as u64,
+                row.get::<_, i64>(4)? as u64,
+                row.get::<_, i64>(5)? as u64,
+                row.get::<_, f64>(6)?,
+            ))
+        })
+        .map_err(db_err)?;
+
+    let mut stats = Vec::new();
+    for row in rows {
+        let (provider, model, total, failed, prompt, completion, avg_latency) = row.map_err(db_err)?;
+        let sorted: Vec<u64> = latencies
+            .query_map(params![since, provider, model], |row| row.get::<_, i64>(0))
+            .map_err(db_err)?
+            .filter_map(|l| l.ok())
+            .map(|l| l as u64)
+            .collect();
+        let error_rate = if total == 0 { 0.0 } else { failed as f32 / total as f32 };
+        stats.push(ModelUsageStats {
+            provider,
+            model,
+            total_requests: total,
+            failed_requests: failed,
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+            avg_response_time_ms: avg_latency.round() as u64,
+            p50_response_time_ms: percentile(&sorted, 50.0),
+            p95_response_time_ms: percentile(&sorted, 95.0),
+            p99_response_time_ms: percentile(&sorted, 99.0),
+            success_rate: 1.0 - error_rate,
+            error_rate,
+        });
+    }
+    Ok(stats)
+}
+
+/// Usage per provider and model since `since` (Unix milliseconds), after
+/// ingesting any gateway logs written since the last call.
+pub fn collect_usage(since: i64) -> Result<Vec<ModelUsageStats>, String> {
+    let mut conn = open_database()?;
+    if let Err(e) = ingest_gateway_logs(&mut conn) {
+        log::warn!(error:% = e; "gateway log ingest failed");
+    }
+    aggregate(&conn, since)
+}
+
+/// Usage per provider and model over `window` (the last day by default),
+/// busiest first.
+#[tauri::command]
+pub async fn get_model_usage_stats(window: Option<UsageWindow>) -> Result<Vec<ModelUsageStats>, String> {
+    collect_usage(window.unwrap_or_default().since())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempHome;
+    use std::io::Write;
+
+    fn line(model: &str) -> String {
+        format!("{{\"model\":\"{}\",\"provider\":\"openai\",\"usage\":{{\"prompt_tokens\":3}}}}\n", model)
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM requests", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn appended_lines_are_ingested_once() {
+        let home = TempHome::new();
+        let log = home.path().join("gateway.log");
+        append(&log, &format!("{}{}", line("a"), line("b")));
+
+        let mut conn = open_database().unwrap();
+        assert_eq!(ingest_file(&mut conn, &log).unwrap(), 2);
+        assert_eq!(ingest_file(&mut conn, &log).unwrap(), 0);
+
+        // A partial last line waits for the next ingest
+        append(&log, &line("c")[..10]);
+        assert_eq!(ingest_file(&mut conn, &log).unwrap(), 0);
+        append(&log, &line("c")[10..]);
+        assert_eq!(ingest_file(&mut conn, &log).unwrap(), 1);
+        assert_eq!(count(&conn), 3);
+    }
+
+    #[test]
+    fn rotated_log_keeps_its_offset() {
+        let home = TempHome::new();
+        let log = home.path().join("gateway.log");
+        append(&log, &line("a"));
+
+        let mut conn = open_database().unwrap();
+        assert_eq!(ingest_file(&mut conn, &log).unwrap(), 1);
+
+        // Rotation renames the file and starts a new one at the same path
+        let rotated = home.path().join("gateway.log.1");
+        fs::rename(&log, &rotated).unwrap();
+        append(&rotated, &line("b"));
+        append(&log, &line("c"));
+
+        assert_eq!(ingest_file(&mut conn, &rotated).unwrap(), 1);
+        assert_eq!(ingest_file(&mut conn, &log).unwrap(), 1);
+        assert_eq!(count(&conn), 3);
+    }
+
+    #[test]
+    fn concurrent_ingests_count_each_line_once() {
+        let home = TempHome::new();
+        let logs = home.path().join(LOGS_DIR);
+        fs::create_dir_all(&logs).unwrap();
+        append(&logs.join("gateway.log"), &(0..200).map(|_| line("a")).collect::<String>());
+
+        let threads: Vec<_> = (0..4).map(|_| std::thread::spawn(|| collect_usage(0).unwrap())).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let stats = collect_usage(0).unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].total_requests, 200);
+    }
+
+    #[test]
+    fn log_lines_are_parsed_in_every_shape() {
+        let openai = parse_line(
+            r#"{"time":"2026-01-02T03:04:05Z","provider":"openai","model":"gpt-4o","usage":{"prompt_tokens":10,"completion_tokens":5},"latency_ms":120}"#,
+            None,
+        )
+        .unwrap();
+        assert_eq!(openai.timestamp, 1_767_323_045_000);
+        assert_eq!(openai.provider.as_deref(), Some("openai"));
+        assert_eq!((openai.prompt_tokens, openai.completion_tokens, openai.latency_ms), (Some(10), Some(5), Some(120)));
+        assert!(openai.success);
+
+        let anthropic = parse_line(
+            r#"{"ts":1767323045,"llm":{"model":"claude","provider":"anthropic"},"usage":{"input_tokens":7,"output_tokens":3},"status":529}"#,
+            None,
+        )
+        .unwrap();
+        assert_eq!(anthropic.timestamp, 1_767_323_045_000);
+        assert_eq!(anthropic.model, "claude");
+        assert_eq!(anthropic.prompt_tokens, Some(7));
+        assert!(!anthropic.success);
+
+        let failed = parse_line(r#"{"ts":1767323045123,"model":"m","durationMs":80.4,"error":"boom"}"#, None).unwrap();
+        assert_eq!(failed.timestamp, 1_767_323_045_123);
+        assert_eq!(failed.latency_ms, Some(80));
+        assert!(!failed.success);
+        let ok = parse_line(r#"{"ts":1,"model":"m","duration_ms":1,"error":false,"ok":true}"#, None).unwrap();
+        assert!(ok.success);
+
+        // Not model calls
+        assert!(parse_line(r#"{"ts":1,"msg":"gateway started"}"#, None).is_none());
+        assert!(parse_line(r#"{"ts":1,"model":"m"}"#, None).is_none());
+        assert!(parse_line("plain text", None).is_none());
+    }
+
+    #[test]
+    fn lines_without_a_timestamp_take_the_fallback_or_are_skipped() {
+        let line = r#"{"model":"m","latency_ms":5}"#;
+        assert!(parse_line(line, None).is_none());
+        assert_eq!(parse_line(line, Some(42)).unwrap().timestamp, 42);
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let latencies: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&latencies, 50.0), Some(50));
+        assert_eq!(percentile(&latencies, 95.0), Some(95));
+        assert_eq!(percentile(&latencies, 99.0), Some(99));
+        assert_eq!(percentile(&[7], 99.0), Some(7));
+        assert_eq!(percentile(&[1, 2, 3], 0.0), Some(1));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn aggregate_groups_by_model_with_error_rate_and_percentiles() {
+        let _home = TempHome::new();
+        let conn = open_database().unwrap();
+        let insert = |ts: i64, model: &str, latency: Option<i64>, success: bool| {
+            conn.execute(
+                "INSERT INTO requests (ts, provider, model, prompt_tokens, completion_tokens, latency_ms, success)
+                 VALUES (?1, 'openai', ?2, 10, 5, ?3, ?4)",
+                params![ts, model, latency, success],
+            )
+            .unwrap();
+        };
+        for i in 1..=20 {
+            insert(1000, "busy", Some(i * 10), i % 4 != 0);
+        }
+        insert(1000, "busy", None, true);
+        insert(1000, "quiet", Some(30), true);
+        // Before the window
+        insert(10, "quiet", Some(9000), false);
+
+        let stats = aggregate(&conn, 500).unwrap();
+        let models: Vec<&str> = stats.iter().map(|s| s.model.as_str()).collect();
+        assert_eq!(models, ["busy", "quiet"]);
+
+        let busy = &stats[0];
+        assert_eq!(busy.total_requests, 21);
+        assert_eq!(busy.failed_requests, 5);
+        assert_eq!(busy.total_tokens, 21 * 15);
+        assert!((busy.error_rate - 5.0 / 21.0).abs() < 1e-6);
+        assert!((busy.success_rate + busy.error_rate - 1.0).abs() < 1e-6);
+        assert_eq!(busy.avg_response_time_ms, 105);
+        assert_eq!(busy.p50_response_time_ms, Some(100));
+        assert_eq!(busy.p95_response_time_ms, Some(190));
+        assert_eq!(busy.p99_response_time_ms, Some(200));
+
+        let quiet = &stats[1];
+        assert_eq!((quiet.total_requests, quiet.failed_requests), (1, 0));
+        assert_eq!(quiet.p99_response_time_ms, Some(30));
+    }
+}
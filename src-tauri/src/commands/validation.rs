@@ -0,0 +1,529 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::net::{Ipv4Addr, TcpListener};
+use std::process::Command;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use super::config::{
+    DiscordIntent, FallbackTrigger, OpenClawConfig, PlatformEntry, SlackMode, WhatsAppPairing,
+};
+use super::config_store::{get_openclaw_dir, load_stored_config};
+use super::logging::run_command;
+use super::models::{get_available_providers, AuthStyle};
+use super::secrets::is_secret_ref;
+
+/// PID file of the running gateway, in the OpenClaw dir.
+const GATEWAY_PID_FILE: &str = "gateway.pid";
+/// `container_name` in the generated docker-compose.yml.
+const CONTAINER_NAME: &str = "openclaw";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDiagnostic {
+    /// JSON pointer of the offending value, e.g. `/platforms/1/bot_token`.
+    pub pointer: String,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ConfigValidationResult {
+    pub valid: bool,
+    pub errors: Vec<ConfigDiagnostic>,
+    pub warnings: Vec<ConfigDiagnostic>,
+}
+
+impl ConfigValidationResult {
+    fn error(&mut self, pointer: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(ConfigDiagnostic {
+            pointer: pointer.into(),
+            severity: DiagnosticSeverity::Error,
+            message: message.into(),
+        });
+    }
+
+    fn warning(&mut self, pointer: impl Into<String>, message: impl Into<String>) {
+        self.warnings.push(ConfigDiagnostic {
+            pointer: pointer.into(),
+            severity: DiagnosticSeverity::Warning,
+            message: message.into(),
+        });
+    }
+}
+
+/// Checks what the config means rather than how it parses. Every problem is
+/// reported, not just the first one.
+pub async fn validate_config(config: &OpenClawConfig) -> ConfigValidationResult {
+    let mut result = ConfigValidationResult::default();
+
+    check_model(config, &mut result).await;
+    check_endpoint(config, &mut result);
+    check_fallbacks(config, &mut result).await;
+    check_gateway_port(config, &mut result);
+    check_platforms(config, &mut result);
+
+    result.valid = result.errors.is_empty();
+    result
+}
+
+async fn check_model(config: &OpenClawConfig, result: &mut ConfigValidationResult) {
+    let providers = get_available_providers().await.unwrap_or_default();
+
+    let Some(provider_id) = config.model_provider.as_deref() else {
+        if config.model_name.is_some() {
+            result.error("/model_provider", "A model is set but no provider is selected");
+        }
+        return;
+    };
+    let Some(provider) = providers.iter().find(|p| p.id == provider_id) else {
+        result.error(
+            "/model_provider",
+            format!("Unknown provider \"{}\"", provider_id),
+        );
+        return;
+    };
+
+    match config.model_name.as_deref() {
+        None => result.warning(
+            "/model_name",
+            format!("No model selected; {} will be used", provider.default_model),
+        ),
+        // Providers ship models faster than the catalog is updated
+        Some(model) if !provider.models.iter().any(|m| m == model) => result.warning(
+            "/model_name",
+            format!("Model \"{}\" is not a known {} model", model, provider.name),
+        ),
+        Some(_) => {}
+    }
+}
+
+fn check_endpoint(config: &OpenClawConfig, result: &mut ConfigValidationResult) {
+    if let Some(endpoint) = config.api_endpoint.as_deref() {
+        check_url("/api_endpoint", endpoint, result);
+    }
+}
+
+fn check_url(pointer: &str, endpoint: &str, result: &mut ConfigValidationResult) {
+    match reqwest::Url::parse(endpoint) {
+        Ok(url) if !matches!(url.scheme(), "http" | "https") => result.error(
+            pointer,
+            format!("Endpoint must use http or https, not {}", url.scheme()),
+        ),
+        Ok(url) if url.host_str().is_none() => result.error(pointer, "Endpoint has no host"),
+        Ok(url) if url.scheme() == "http" && !is_local_host(url.host_str()) => result.warning(
+            pointer,
+            "Endpoint uses plain http; the API key will be sent unencrypted",
+        ),
+        Ok(_) => {}
+        Err(e) => result.error(pointer, format!("Invalid URL: {}", e)),
+    }
+}
+
+async fn check_fallbacks(config: &OpenClawConfig, result: &mut ConfigValidationResult) {
+    if config.model_fallbacks.is_empty() {
+        return;
+    }
+    if config.model_provider.is_none() {
+        result.error("/model_fallbacks", "Fallback models need a primary provider to fall back from");
+    }
+    let providers = get_available_providers().await.unwrap_or_default();
+
+    let mut seen: Vec<(&str, &str)> = config
+        .model_provider
+        .as_deref()
+        .zip(config.model_name.as_deref())
+        .into_iter()
+        .collect();
+    for (i, hop) in config.model_fallbacks.iter().enumerate() {
+        let at = |field: &str| format!("/model_fallbacks/{}/{}", i, field);
+
+        let provider = providers.iter().find(|p| p.id == hop.provider);
+        match provider {
+            _ if hop.provider.trim().is_empty() => result.error(at("provider"), "Provider is required"),
+            None => result.error(at("provider"), format!("Unknown provider \"{}\"", hop.provider)),
+            Some(_) => {}
+        }
+        if hop.model.trim().is_empty() {
+            result.error(at("model"), "Model is required");
+        } else if let Some(p) = provider.filter(|p| !p.models.iter().any(|m| m == &hop.model)) {
+            result.warning(at("model"), format!("Model \"{}\" is not a known {} model", hop.model, p.name));
+        }
+
+        if let Some(endpoint) = hop.api_endpoint.as_deref() {
+            check_url(&at("api_endpoint"), endpoint, result);
+        }
+        let has_key = hop.api_key.as_deref().is_some_and(|k| !k.is_empty())
+            || (config.model_provider.as_deref() == Some(hop.provider.as_str())
+                && config.api_key.as_deref().is_some_and(|k| !k.is_empty()));
+        if !has_key && provider.is_some_and(|p| p.auth != AuthStyle::None) {
+            result.warning(at("api_key"), format!("No API key for {}", hop.provider));
+        }
+
+        if hop.triggers.is_empty() {
+            result.error(at("triggers"), "Without triggers this fallback is never used");
+        }
+        for (j, trigger) in hop.triggers.iter().enumerate() {
+            if matches!(trigger, FallbackTrigger::Timeout { after_ms: 0 }) {
+                result.error(
+                    format!("/model_fallbacks/{}/triggers/{}/after_ms", i, j),
+                    "Timeout must be greater than zero",
+                );
+            }
+        }
+
+        if seen.contains(&(hop.provider.as_str(), hop.model.as_str())) {
+            result.warning(
+                at("model"),
+                format!("{} / {} already appears earlier in the chain", hop.provider, hop.model),
+            );
+        } else {
+            seen.push((&hop.provider, &hop.model));
+        }
+    }
+}
+
+fn is_local_host(host: Option<&str>) -> bool {
+    matches!(host, Some("localhost" | "127.0.0.1" | "[::1]" | "::1"))
+}
+
+fn check_gateway_port(config: &OpenClawConfig, result: &mut ConfigValidationResult) {
+    let port = config.gateway_port;
+    if port == 0 {
+        result.error("/gateway_port", "Gateway port must be between 1 and 65535");
+        return;
+    }
+    if TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok() {
+        return;
+    }
+
+    match port_owner(port) {
+        Some(owner) if is_gateway_process(&owner, gateway_pid(), container_publishes(port)) => {}
+        Some(owner) => result.error(
+            "/gateway_port",
+            format!("Port {} is in use by {} (PID {})", port, owner.name, owner.pid),
+        ),
+        None => result.warning(
+            "/gateway_port",
+            format!("Port {} is in use by an unidentified process", port),
+        ),
+    }
+}
+
+/// Process listening on a port.
+struct PortOwner {
+    pid: u32,
+    name: String,
+    /// Empty when the OS does not reveal it.
+    command_line: String,
+}
+
+/// The gateway is the process started from the `openclaw` package (directly
+/// or through node), the one recorded in its PID file, or, for Docker
+/// installs, the proxy publishing the OpenClaw container's port.
+fn is_gateway_process(owner: &PortOwner, gateway_pid: Option<u32>, published_by_container: bool) -> bool {
+    gateway_pid == Some(owner.pid)
+        || owner.command_line.to_lowercase().contains("openclaw")
+        || (published_by_container && owner.name.to_lowercase().contains("docker"))
+}
+
+fn gateway_pid() -> Option<u32> {
+    let path = get_openclaw_dir().ok()?.join(GATEWAY_PID_FILE);
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Whether the OpenClaw container publishes `port` on the host.
+fn container_publishes(port: u16) -> bool {
+    let Ok(output) = run_command(Command::new("docker").args(["port", CONTAINER_NAME])) else {
+        return false;
+    };
+    let suffix = format!(":{}", port);
+    output.status.success()
+        && String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|l| l.trim().ends_with(&suffix))
+}
+
+/// The process listening on `port`, when the OS tools can tell us.
+fn port_owner(port: u16) -> Option<PortOwner> {
+    let pid = listening_pid(port)?;
+    let mut system = System::new();
+    let target = Pid::from_u32(pid);
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[target]),
+        true,
+        ProcessRefreshKind::nothing().with_cmd(UpdateKind::Always),
+    );
+    let process = system.process(target)?;
+    let command_line = process
+        .cmd()
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(PortOwner {
+        pid,
+        name: process.name().to_string_lossy().to_string(),
+        command_line,
+    })
+}
+
+fn listening_pid(port: u16) -> Option<u32> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = run_command(Command::new("netstat").args(["-ano", "-p", "TCP"])).ok()?;
+        let suffix = format!(":{}", port);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .map(|l| l.split_whitespace().collect::<Vec<_>>())
+            .find(|cols| cols.len() >= 5 && cols[1].ends_with(&suffix) && cols[3] == "LISTENING")
+            .and_then(|cols| cols[4].parse().ok())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let output = run_command(
+            Command::new("lsof").args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-Fp"]),
+        )
+        .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .find_map(|l| l.strip_prefix('p'))
+            .and_then(|pid| pid.parse().ok())
+    }
+}
+
+fn check_platforms(config: &OpenClawConfig, result: &mut ConfigValidationResult) {
+    let mut seen = HashSet::new();
+    for (i, entry) in config.platforms.iter().enumerate() {
+        let at = |field: &str| format!("/platforms/{}/{}", i, field);
+        let platform = entry.platform().trim();
+        if platform.is_empty() {
+            result.error(at("platform"), "Platform name is required");
+            continue;
+        }
+        if !seen.insert(platform.to_lowercase()) {
+            result.error(
+                at("platform"),
+                format!("Platform \"{}\" is configured more than once", platform),
+            );
+        }
+        if let (Some(field), Some(token)) = (PlatformEntry::token_field(platform), entry.token()) {
+            if token.trim().is_empty() {
+                result.error(at(field), format!("{} token is empty", platform));
+            }
+        }
+
+        match entry {
+            PlatformEntry::Telegram(c) => {
+                let well_formed = c
+                    .bot_token
+                    .split_once(':')
+                    .is_some_and(|(id, _)| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()));
+                if !c.bot_token.trim().is_empty() && !is_secret_ref(&c.bot_token) && !well_formed {
+                    result.warning(at("bot_token"), "Telegram bot tokens look like 123456:ABC-DEF…");
+                }
+            }
+            PlatformEntry::Discord(c) => {
+                if c.intents.is_empty() {
+                    result.error(at("intents"), "Discord needs at least one gateway intent");
+                } else if !c.intents.contains(&DiscordIntent::MessageContent) {
+                    result.warning(
+                        at("intents"),
+                        "Without the message_content intent the bot only sees messages that mention it",
+                    );
+                }
+                for (j, guild) in c.guild_allowlist.iter().enumerate() {
+                    if guild.is_empty() || !guild.bytes().all(|b| b.is_ascii_digit()) {
+                        result.error(
+                            format!("/platforms/{}/guild_allowlist/{}", i, j),
+                            format!("\"{}\" is not a Discord guild id", guild),
+                        );
+                    }
+                }
+            }
+            PlatformEntry::Slack(c) => {
+                let (field, value, what) = match c.mode {
+                    SlackMode::Socket => ("app_token", &c.app_token, "Socket mode needs an app-level token"),
+                    SlackMode::Http => ("signing_secret", &c.signing_secret, "HTTP mode needs the signing secret"),
+                };
+                if value.as_deref().is_none_or(|v| v.trim().is_empty()) {
+                    result.error(at(field), what);
+                }
+                let prefixed = |value: &str, prefix: &str| is_secret_ref(value) || value.starts_with(prefix);
+                if !c.bot_token.trim().is_empty() && !prefixed(&c.bot_token, "xoxb-") {
+                    result.warning(at("bot_token"), "Slack bot tokens start with xoxb-");
+                }
+                if let Some(app_token) = c.app_token.as_deref().filter(|t| !t.is_empty()) {
+                    if !prefixed(app_token, "xapp-") {
+                        result.warning(at("app_token"), "Slack app-level tokens start with xapp-");
+                    }
+                }
+            }
+            PlatformEntry::WhatsApp(c) => {
+                let phone = c.phone_number.as_deref().map(str::trim).unwrap_or_default();
+                if c.pairing == WhatsAppPairing::PhoneCode && phone.is_empty() {
+                    result.error(at("phone_number"), "Pairing by code needs the phone number");
+                }
+                if !phone.is_empty() && !is_phone_number(phone) {
+                    result.error(
+                        at("phone_number"),
+                        "Phone number must be in international format, e.g. +14155550123",
+                    );
+                }
+            }
+            PlatformEntry::Other(_) => {}
+        }
+    }
+}
+
+/// `+` followed by 8 to 15 digits (E.164).
+fn is_phone_number(value: &str) -> bool {
+    value
+        .strip_prefix('+')
+        .is_some_and(|digits| (8..=15).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Validates `config` when given (e.g. the unsaved Settings form), otherwise
+/// the config on disk.
+#[tauri::command]
+pub async fn validate_openclaw_config(
+    config: Option<OpenClawConfig>,
+) -> Result<ConfigValidationResult, String> {
+    let config = match config {
+        Some(config) => config,
+        None => load_stored_config()?.unwrap_or_default(),
+    };
+    Ok(validate_config(&config).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::migrations::CURRENT_SCHEMA_VERSION;
+    use crate::commands::test_support::TempHome;
+
+    fn owner(pid: u32, name: &str, command_line: &str) -> PortOwner {
+        PortOwner {
+            pid,
+            name: name.to_string(),
+            command_line: command_line.to_string(),
+        }
+    }
+
+    #[test]
+    fn gateway_is_recognized_by_command_line_or_pid() {
+        let npm = owner(41, "node", "node /usr/lib/node_modules/openclaw/dist/index.js gateway start");
+        assert!(is_gateway_process(&npm, None, false));
+        assert!(is_gateway_process(&owner(42, "openclaw", "openclaw gateway"), None, false));
+        assert!(is_gateway_process(&owner(43, "node", ""), Some(43), false));
+    }
+
+    #[test]
+    fn unrelated_node_and_docker_processes_are_not_the_gateway() {
+        assert!(!is_gateway_process(&owner(50, "node", "node server.js"), Some(7), false));
+        assert!(!is_gateway_process(&owner(51, "docker-proxy", "docker-proxy -host-port 18789"), None, false));
+        assert!(is_gateway_process(&owner(51, "docker-proxy", "docker-proxy -host-port 18789"), None, true));
+    }
+
+    fn config(value: serde_json::Value) -> OpenClawConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn pointers(diagnostics: &[ConfigDiagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.pointer.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn unknown_providers_are_errors_at_their_pointer() {
+        let _home = TempHome::new();
+        let config = config(serde_json::json!({
+            "model_provider": "nonexistent",
+            "model_name": "x",
+            "model_fallbacks": [
+                {"provider": "openai", "model": "gpt-4o"},
+                {"provider": "also-missing", "model": "y"}
+            ]
+        }));
+        let mut result = ConfigValidationResult::default();
+        check_model(&config, &mut result).await;
+        check_fallbacks(&config, &mut result).await;
+        assert_eq!(
+            pointers(&result.errors),
+            ["/model_provider", "/model_fallbacks/1/provider"]
+        );
+        assert!(result.errors[0].message.contains("nonexistent"));
+    }
+
+    #[test]
+    fn endpoints_must_be_http_urls_with_a_host() {
+        let mut result = ConfigValidationResult::default();
+        check_url("/api_endpoint", "not a url", &mut result);
+        check_url("/model_fallbacks/0/api_endpoint", "ftp://example.com", &mut result);
+        check_url("/api_endpoint", "https://api.example.com/v1", &mut result);
+        check_url("/api_endpoint", "http://localhost:11434", &mut result);
+        check_url("/api_endpoint", "http://api.example.com", &mut result);
+        assert_eq!(
+            pointers(&result.errors),
+            ["/api_endpoint", "/model_fallbacks/0/api_endpoint"]
+        );
+        assert_eq!(pointers(&result.warnings), ["/api_endpoint"]);
+        assert!(result.warnings[0].message.contains("plain http"));
+    }
+
+    #[test]
+    fn duplicate_platforms_and_empty_tokens_are_reported() {
+        let config = config(serde_json::json!({
+            "platforms": [
+                {"platform": "telegram", "bot_token": "123:abc"},
+                {"platform": "Telegram", "bot_token": "456:def"},
+                {"platform": "discord", "bot_token": "  "},
+                {"platform": "slack", "bot_token": "xoxb-1", "mode": "socket"}
+            ]
+        }));
+        let mut result = ConfigValidationResult::default();
+        check_platforms(&config, &mut result);
+        assert_eq!(
+            pointers(&result.errors),
+            ["/platforms/1/platform", "/platforms/2/bot_token", "/platforms/3/app_token"]
+        );
+        assert!(result.errors[0].message.contains("more than once"));
+        assert_eq!(result.errors[1].message, "discord token is empty");
+    }
+
+    #[tokio::test]
+    async fn every_problem_is_reported_with_its_pointer() {
+        let _home = TempHome::new();
+        let config = config(serde_json::json!({
+            "schema_version": CURRENT_SCHEMA_VERSION,
+            "model_name": "gpt-4o",
+            "api_endpoint": "mailto:someone",
+            "gateway_port": 0,
+            "model_fallbacks": [{"provider": "openai", "model": "", "triggers": []}],
+            "platforms": [{"platform": "discord", "bot_token": "t", "intents": [], "guild_allowlist": ["abc"]}]
+        }));
+        let result = validate_config(&config).await;
+        assert!(!result.valid);
+        assert_eq!(
+            pointers(&result.errors),
+            [
+                "/model_provider",
+                "/api_endpoint",
+                "/model_fallbacks",
+                "/model_fallbacks/0/model",
+                "/model_fallbacks/0/triggers",
+                "/gateway_port",
+                "/platforms/0/intents",
+                "/platforms/0/guild_allowlist/0",
+            ]
+        );
+    }
+}
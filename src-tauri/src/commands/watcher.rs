@@ -0,0 +1,270 @@
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::config::OpenClawConfig;
+use super::config_store::{
+    diff_json, get_config_path, get_openclaw_dir, mask_change, revision_of, ConfigChange, CONFIG_FILE,
+};
+use super::logging::emit;
+use super::migrations::migrate_document;
+use super::secrets::resolve_secrets;
+
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+
+/// Editors and atomic renames produce bursts of events for one save.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Only says what changed; listeners that want the new config read it with
+/// `read_openclaw_config`, so resolved secrets never go out on the event bus.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChangedPayload {
+    /// `None` when the file was deleted.
+    pub revision: Option<String>,
+    /// Changes since the previous version, on the raw document, with secret
+    /// values masked.
+    pub changes: Vec<ConfigChange>,
+    /// Why the new file cannot be loaded, if it cannot.
+    pub error: Option<String>,
+}
+
+struct Snapshot {
+    revision: Option<String>,
+    doc: serde_json::Value,
+    error: Option<String>,
+}
+
+fn read_snapshot(path: &Path) -> Snapshot {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Snapshot {
+            revision: None,
+            doc: serde_json::Value::Object(Default::default()),
+            error: None,
+        };
+    };
+    let revision = Some(revision_of(&content));
+    let doc: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(doc) => doc,
+        Err(e) => {
+            return Snapshot {
+                revision,
+                doc: serde_json::Value::Null,
+                error: Some(format!("Failed to parse config: {}", e)),
+            }
+        }
+    };
+
+    // Loaded the way `read_openclaw_config` does, to report why it would fail
+    let mut migrated = doc.clone();
+    let error = migrate_document(&mut migrated)
+        .and_then(|_| {
+            serde_json::from_value::<OpenClawConfig>(migrated)
+                .map_err(|e| format!("Failed to parse config: {}", e))
+        })
+        .and_then(|mut config| resolve_secrets(&mut config))
+        .err();
+    Snapshot {
+        revision,
+        doc,
+        error,
+    }
+}
+
+/// Re-reads the file and describes how it differs from `last`, which is
+/// then advanced. `None` when the contents did not change.
+fn next_change(last: &mut Snapshot, path: &Path) -> Option<ConfigChangedPayload> {
+    let current = read_snapshot(path);
+    if current.revision == last.revision {
+        return None;
+    }
+    let changes = if current.doc.is_null() {
+        Vec::new()
+    } else {
+        diff_json(&last.doc, &current.doc).into_iter().map(mask_change).collect()
+    };
+    let payload = ConfigChangedPayload {
+        revision: current.revision.clone(),
+        changes,
+        error: current.error.clone(),
+    };
+
+    // Keep diffing against the last readable version
+    if !current.doc.is_null() {
+        *last = current;
+    } else {
+        last.revision = current.revision;
+    }
+    Some(payload)
+}
+
+/// Waits until no event has arrived for `quiet`, returning how many were
+/// swallowed.
+fn settle<T>(rx: &mpsc::Receiver<T>, quiet: Duration) -> usize {
+    let mut swallowed = 0;
+    while rx.recv_timeout(quiet).is_ok() {
+        swallowed += 1;
+    }
+    swallowed
+}
+
+/// Starts watching `openclaw.json` on a background thread and emits
+/// `config-changed` whenever its contents change, whoever changed it.
+pub fn start(app: AppHandle) -> Result<(), String> {
+    let dir = get_openclaw_dir()?;
+    // The directory has to exist to be watched; the file itself may not yet
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let path = get_config_path()?;
+
+    std::thread::Builder::new()
+        .name("config-watcher".to_string())
+        .spawn(move || watch(app, dir, path))
+        .map_err(|e| format!("Failed to start config watcher: {}", e))?;
+    Ok(())
+}
+
+fn watch(app: AppHandle, dir: PathBuf, path: PathBuf) {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(w) => w,
+        Err(e) => {
+            log::warn!(error:% = e; "config watcher unavailable");
+            return;
+        }
+    };
+    // Watch the directory, not the file: atomic saves replace the inode
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        log::warn!(dir:% = dir.display(), error:% = e; "failed to watch config directory");
+        return;
+    }
+    log::info!(path:% = path.display(); "watching config for changes");
+
+    let mut last = read_snapshot(&path);
+    while let Ok(event) = rx.recv() {
+        let touches_config = match event {
+            Ok(event) => event
+                .paths
+                .iter()
+                .any(|p| p.file_name().and_then(|n| n.to_str()) == Some(CONFIG_FILE)),
+            Err(e) => {
+                log::warn!(error:% = e; "config watcher error");
+                false
+            }
+        };
+        if !touches_config {
+            continue;
+        }
+        settle(&rx, DEBOUNCE);
+
+        let Some(payload) = next_change(&mut last, &path) else {
+            continue;
+        };
+        log::info!(revision:? = payload.revision, changes = payload.changes.len(); "config changed on disk");
+        emit(&app, CONFIG_CHANGED_EVENT, payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempHome;
+    use serde_json::json;
+    use std::time::Instant;
+
+    fn write(path: &Path, doc: serde_json::Value) {
+        fs::write(path, doc.to_string()).unwrap();
+    }
+
+    fn paths(payload: &ConfigChangedPayload) -> Vec<(&str, &str)> {
+        payload.changes.iter().map(|c| (c.path.as_str(), c.kind.as_str())).collect()
+    }
+
+    #[test]
+    fn diff_lists_leaf_changes() {
+        let old = json!({"model_name": "a", "gateway_port": 1, "platforms": [{"platform": "slack"}]});
+        let new = json!({"model_name": "b", "platforms": [{"platform": "slack"}, {"platform": "discord"}], "a/b": 1});
+        let changes: Vec<_> = diff_json(&old, &new)
+            .into_iter()
+            .map(|c| (c.path, c.kind, c.old, c.new))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("/model_name".to_string(), "changed".to_string(), Some(json!("a")), Some(json!("b"))),
+                ("/gateway_port".to_string(), "removed".to_string(), Some(json!(1)), None),
+                ("/platforms/1".to_string(), "added".to_string(), None, Some(json!({"platform": "discord"}))),
+                ("/a~1b".to_string(), "added".to_string(), None, Some(json!(1))),
+            ]
+        );
+        assert!(diff_json(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn changes_are_reported_without_the_config_or_secrets() {
+        let home = TempHome::new();
+        let path = home.path().join(CONFIG_FILE);
+        write(&path, json!({"schema_version": 2, "gateway_port": 18789}));
+        let mut last = read_snapshot(&path);
+        assert!(next_change(&mut last, &path).is_none());
+
+        write(&path, json!({"schema_version": 2, "gateway_port": 18790, "api_key": "sk-edited-by-hand"}));
+        let payload = next_change(&mut last, &path).unwrap();
+        assert_eq!(payload.revision, last.revision);
+        assert_eq!(payload.error, None);
+        assert_eq!(paths(&payload), [("/gateway_port", "changed"), ("/api_key", "added")]);
+        let sent = serde_json::to_string(&payload).unwrap();
+        assert!(!sent.contains("sk-edited-by-hand"), "{}", sent);
+        assert!(!sent.contains("\"config\""), "{}", sent);
+    }
+
+    #[test]
+    fn unreadable_file_reports_an_error_and_keeps_the_last_good_version() {
+        let home = TempHome::new();
+        let path = home.path().join(CONFIG_FILE);
+        write(&path, json!({"schema_version": 2, "gateway_port": 18789}));
+        let mut last = read_snapshot(&path);
+
+        fs::write(&path, "{\"gateway_port\": ").unwrap();
+        let broken = next_change(&mut last, &path).unwrap();
+        assert!(broken.error.as_deref().unwrap().starts_with("Failed to parse config"), "{:?}", broken.error);
+        assert!(broken.changes.is_empty());
+        assert!(next_change(&mut last, &path).is_none());
+
+        // Diffed against the version before the broken save
+        write(&path, json!({"schema_version": 2, "gateway_port": 18790}));
+        let fixed = next_change(&mut last, &path).unwrap();
+        assert_eq!(fixed.error, None);
+        assert_eq!(paths(&fixed), [("/gateway_port", "changed")]);
+
+        fs::remove_file(&path).unwrap();
+        let deleted = next_change(&mut last, &path).unwrap();
+        assert_eq!(deleted.revision, None);
+        assert_eq!(deleted.error, None);
+        assert_eq!(paths(&deleted), [("/schema_version", "removed"), ("/gateway_port", "removed")]);
+    }
+
+    #[test]
+    fn a_burst_of_events_settles_into_one() {
+        let (tx, rx) = mpsc::channel();
+        let quiet = Duration::from_millis(100);
+        std::thread::spawn(move || {
+            for _ in 0..5 {
+                tx.send(()).unwrap();
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            std::thread::sleep(Duration::from_millis(400));
+            let _ = tx.send(());
+        });
+
+        // The first event wakes the watcher; the rest are swallowed
+        rx.recv().unwrap();
+        let started = Instant::now();
+        assert_eq!(settle(&rx, quiet), 4);
+        assert!(started.elapsed() >= quiet);
+        // The later save is a new burst
+        assert!(rx.recv_timeout(Duration::from_secs(1)).is_ok());
+    }
+}
@@ -1,40 +1,124 @@
 mod commands;
 
-use commands::config::{detect_npm_registry, read_openclaw_config, test_api_connection, write_openclaw_config};
+use commands::backup::{diff_config_backup, list_config_backups, restore_config_backup};
+use commands::api_test::{test_api_connection, test_model_chain};
+use commands::config::{detect_npm_registry, get_config_revision, read_openclaw_config, write_openclaw_config};
+use commands::cost::{get_budgets, get_cost_report, save_budgets};
 use commands::detect::detect_environment;
 use commands::download::download_dependency;
+use commands::effective::get_effective_config;
 use commands::install::{install_dependency, install_openclaw};
-use commands::models::{get_available_providers, get_model_presets, get_model_usage_stats, validate_model_parameters};
+use commands::logging::export_logs;
+use commands::migrations::migrate_config;
+use commands::models::{clamp_model_parameters, get_available_providers, validate_model_parameters};
+use commands::ollama::{
+    check_ollama_model, detect_ollama, ollama_delete_model, ollama_list_models, ollama_pull_model, ollama_start,
+    ollama_stop,
+};
+use commands::platforms::{get_platform_schemas, test_platform_connection};
+use commands::presets::{apply_model_preset, delete_model_preset, get_model_presets, save_model_preset};
+use commands::profiles::{activate_profile, delete_profile, list_profiles, save_profile};
+use commands::secrets::{get_secret_backend, migrate_secrets};
 use commands::service::{gateway_start, gateway_stop, gateway_restart, gateway_status};
+use commands::support::create_support_bundle;
+use commands::discovery::discover_models;
+use commands::chat::{cancel_chat_preview, chat_preview};
+use commands::catalog::{delete_custom_provider, get_catalog_status, refresh_provider_catalog, save_custom_provider};
 use commands::doctor::run_doctor;
+use commands::transfer::{export_openclaw_config, import_openclaw_config};
 use commands::troubleshoot::{fix_issue, run_diagnostics};
+use commands::usage::get_model_usage_stats;
+use commands::validation::validate_openclaw_config;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let handler = tauri::generate_handler![
+        detect_environment,
+        download_dependency,
+        install_dependency,
+        install_openclaw,
+        write_openclaw_config,
+        read_openclaw_config,
+        get_config_revision,
+        get_effective_config,
+        validate_openclaw_config,
+        export_openclaw_config,
+        import_openclaw_config,
+        list_config_backups,
+        diff_config_backup,
+        restore_config_backup,
+        migrate_config,
+        list_profiles,
+        save_profile,
+        activate_profile,
+        delete_profile,
+        detect_npm_registry,
+        test_api_connection,
+        test_model_chain,
+        test_platform_connection,
+        get_platform_schemas,
+        chat_preview,
+        cancel_chat_preview,
+        gateway_start,
+        gateway_stop,
+        gateway_restart,
+        gateway_status,
+        run_doctor,
+        get_available_providers,
+        discover_models,
+        get_catalog_status,
+        refresh_provider_catalog,
+        save_custom_provider,
+        delete_custom_provider,
+        get_model_presets,
+        save_model_preset,
+        delete_model_preset,
+        apply_model_preset,
+        detect_ollama,
+        ollama_start,
+        ollama_stop,
+        ollama_list_models,
+        ollama_pull_model,
+        ollama_delete_model,
+        check_ollama_model,
+        get_model_usage_stats,
+        get_cost_report,
+        get_budgets,
+        save_budgets,
+        validate_model_parameters,
+        clamp_model_parameters,
+        run_diagnostics,
+        fix_issue,
+        export_logs,
+        create_support_bundle,
+        get_secret_backend,
+        migrate_secrets,
+    ];
+
     tauri::Builder::default()
+        .plugin(commands::logging::plugin())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
-        .invoke_handler(tauri::generate_handler![
-            detect_environment,
-            download_dependency,
-            install_dependency,
-            install_openclaw,
-            write_openclaw_config,
-            read_openclaw_config,
-            detect_npm_registry,
-            test_api_connection,
-            gateway_start,
-            gateway_stop,
-            gateway_restart,
-            gateway_status,
-            run_doctor,
-            get_available_providers,
-            get_model_presets,
-            get_model_usage_stats,
-            validate_model_parameters,
-            run_diagnostics,
-            fix_issue,
-        ])
+        .setup(|app| {
+            // Before the watcher starts, so the rewrites are not reported as external edits
+            if let Err(e) = commands::migrations::upgrade_stored_config(false) {
+                log::warn!(error:% = e; "config not migrated");
+            }
+            if let Err(e) = commands::secrets::move_plaintext_secrets() {
+                log::warn!(error:% = e; "plaintext secrets not migrated");
+            }
+            if let Err(e) = commands::watcher::start(app.handle().clone()) {
+                log::warn!(error:% = e; "config watcher not started");
+            }
+            if let Err(e) = commands::cost::start_budget_monitor(app.handle().clone()) {
+                log::warn!(error:% = e; "budget monitor not started");
+            }
+            Ok(())
+        })
+        .invoke_handler(move |invoke| {
+            log::info!(target: "command", command:% = invoke.message.command(); "command invoked");
+            handler(invoke)
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -4,7 +4,7 @@ import { useTranslation } from 'react-i18next';
 
 export function ModelManagement() {
   const { t } = useTranslation();
-  const { providers, presets, stats, loading, validateParameters } = useModelManagement();
+  const { providers, presets, stats, loading, validateParameters, clampParameters } = useModelManagement();
   const [selectedProvider, setSelectedProvider] = useState('');
   const [selectedModel, setSelectedModel] = useState('');
   const [parameters, setParameters] = useState<ModelParameters>({
@@ -25,8 +25,15 @@ export function ModelManagement() {
   };
 
   const handleSave = async () => {
-    const isValid = await validateParameters(parameters);
-    if (isValid) {
+    const result = await validateParameters(parameters, selectedProvider, selectedModel);
+    if (result && !result.valid) {
+      const messages = result.violations.map((v) => v.message).join('\n');
+      if (confirm(`${messages}\n\nAdjust parameters to the nearest valid values?`)) {
+        setParameters(await clampParameters(parameters, selectedProvider, selectedModel));
+      }
+      return;
+    }
+    if (result) {
       // TODO: Save to config
       alert('Parameters saved successfully!');
     }
@@ -91,8 +98,14 @@ export function ModelManagement() {
               onClick={() => handlePresetSelect(preset)}
               className="p-3 border rounded-md hover:bg-gray-50 text-left"
             >
-              <div className="font-medium">{preset.name}</div>
-              <div className="text-xs text-gray-500">{preset.description}</div>
+              <div className="font-medium">
+                {preset.i18n_key ? t(`${preset.i18n_key}.name`, { defaultValue: preset.name }) : preset.name}
+              </div>
+              <div className="text-xs text-gray-500">
+                {preset.i18n_key
+                  ? t(`${preset.i18n_key}.description`, { defaultValue: preset.description })
+                  : preset.description}
+              </div>
             </button>
           ))}
         </div>
@@ -39,9 +39,10 @@ export interface ModelConfig {
   model: string;
 }
 
+/** One platform's settings; the fields depend on the platform's schema. */
 export interface PlatformEntry {
   platform: string;
-  token: string;
+  [field: string]: unknown;
 }
 
 interface WizardState {
@@ -19,19 +19,43 @@ export interface ModelParameters {
   stream: boolean;
 }
 
+export interface ParameterViolation {
+  field: string;
+  message: string;
+}
+
+export interface ParameterValidation {
+  valid: boolean;
+  violations: ParameterViolation[];
+}
+
 export interface ModelPreset {
   name: string;
   description: string;
   parameters: ModelParameters;
+  provider?: string | null;
+  model?: string | null;
+  builtin?: boolean;
+  // Translation key of a built-in preset, e.g. presets.creative
+  i18n_key?: string;
 }
 
+export type UsageWindow = 'hour' | 'day' | 'week' | 'month' | 'all';
+
 export interface ModelUsageStats {
   provider: string;
   model: string;
   total_requests: number;
+  failed_requests: number;
+  prompt_tokens: number;
+  completion_tokens: number;
   total_tokens: number;
   avg_response_time_ms: number;
+  p50_response_time_ms: number | null;
+  p95_response_time_ms: number | null;
+  p99_response_time_ms: number | null;
   success_rate: number;
+  error_rate: number;
 }
 
 export function useModelManagement() {
@@ -63,31 +87,66 @@ export function useModelManagement() {
     }
   };
 
-  const loadStats = async () => {
+  const loadStats = async (range?: UsageWindow) => {
     try {
-      const result = await invoke<ModelUsageStats[]>('get_model_usage_stats');
+      const result = await invoke<ModelUsageStats[]>('get_model_usage_stats', { window: range });
       setStats(result);
     } catch (err) {
       console.error('Failed to load stats:', err);
     }
   };
 
-  const validateParameters = async (params: ModelParameters): Promise<boolean> => {
+  const validateParameters = async (
+    params: ModelParameters,
+    provider?: string,
+    model?: string
+  ): Promise<ParameterValidation | null> => {
     try {
-      await invoke<boolean>('validate_model_parameters', { params });
-      return true;
+      const result = await invoke<ParameterValidation>('validate_model_parameters', {
+        params,
+        provider: provider || null,
+        model: model || null,
+      });
+      setError(result.valid ? null : result.violations.map((v) => v.message).join('\n'));
+      return result;
     } catch (err) {
       setError(err as string);
-      return false;
+      return null;
     }
   };
 
+  const clampParameters = async (
+    params: ModelParameters,
+    provider?: string,
+    model?: string
+  ): Promise<ModelParameters> => {
+    return invoke<ModelParameters>('clamp_model_parameters', {
+      params,
+      provider: provider || null,
+      model: model || null,
+    });
+  };
+
   useEffect(() => {
     loadProviders();
     loadPresets();
     loadStats();
   }, []);
 
+  const savePreset = async (preset: ModelPreset) => {
+    await invoke('save_model_preset', { preset });
+    await loadPresets();
+  };
+
+  const deletePreset = async (name: string) => {
+    await invoke('delete_model_preset', { name });
+    await loadPresets();
+  };
+
+  const applyPreset = async (name: string) => {
+    await invoke('apply_model_preset', { name });
+  };
+
   return {
     providers,
     presets,
@@ -98,5 +157,9 @@ export function useModelManagement() {
     loadPresets,
     loadStats,
     validateParameters,
+    clampParameters,
+    savePreset,
+    deletePreset,
+    applyPreset,
   };
 }
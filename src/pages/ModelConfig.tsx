@@ -8,6 +8,34 @@ import { Button } from "@/components/ui/button";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
 import { useWizard } from "@/context/WizardContext";
 
+type FailureKind =
+  | "invalid_key"
+  | "model_not_found"
+  | "rate_limited"
+  | "insufficient_balance"
+  | "region_blocked"
+  | "endpoint_unreachable"
+  | "unknown";
+
+interface ApiTestResult {
+  success: boolean;
+  status: number | null;
+  failure: FailureKind | null;
+  message: string;
+  timings: {
+    dns_ms: number | null;
+    tcp_connect_ms: number | null;
+    tls_handshake_ms: number | null;
+    first_byte_ms: number | null;
+    total_ms: number;
+  };
+  rate_limit: {
+    requests_remaining: number | null;
+    tokens_remaining: number | null;
+    retry_after_secs: number | null;
+  } | null;
+}
+
 const PROVIDERS = [
   { id: "aliyun", name: "阿里云百炼", endpoint: "https://dashscope.aliyuncs.com/compatible-mode/v1", models: ["qwen-max", "qwen-plus", "qwen-turbo"], url: "https://bailian.console.aliyun.com/", primary: true },
   { id: "deepseek", name: "DeepSeek", endpoint: "https://api.deepseek.com/v1", models: ["deepseek-chat", "deepseek-reasoner"], url: "https://platform.deepseek.com/", primary: true },
@@ -30,7 +58,8 @@ export default function ModelConfig() {
   const [showKey, setShowKey] = useState(false);
   const [showMore, setShowMore] = useState(false);
   const [testing, setTesting] = useState(false);
-  const [testResult, setTestResult] = useState<{ ok: boolean; msg: string } | null>(null);
+  const [testResult, setTestResult] = useState<ApiTestResult | null>(null);
+  const [testError, setTestError] = useState<string | null>(null);
 
   const selectedProvider = PROVIDERS.find((p) => p.id === modelConfig.provider);
   const visibleProviders = showMore ? PROVIDERS : PROVIDERS.filter((p) => p.primary);
@@ -44,21 +73,23 @@ export default function ModelConfig() {
       model: prov?.models[0] ?? "",
     });
     setTestResult(null);
+    setTestError(null);
   };
 
   const handleTest = async () => {
     setTesting(true);
     setTestResult(null);
+    setTestError(null);
     try {
-      const msg = await invoke<string>("test_api_connection", {
+      const result = await invoke<ApiTestResult>("test_api_connection", {
         provider: modelConfig.provider,
-        api_key: modelConfig.apiKey,
+        apiKey: modelConfig.apiKey,
         endpoint: modelConfig.endpoint,
         model: modelConfig.model,
       });
-      setTestResult({ ok: true, msg });
+      setTestResult(result);
     } catch (err) {
-      setTestResult({ ok: false, msg: String(err) });
+      setTestError(String(err));
     } finally {
       setTesting(false);
     }
@@ -196,12 +227,46 @@ export default function ModelConfig() {
                   {t("modelConfig.testConnection")}
                 </Button>
                 {testResult && (
-                  <span className={`text-xs flex items-center gap-1 ${testResult.ok ? "text-green-600" : "text-red-500"}`}>
-                    {testResult.ok ? <CheckCircle2 className="h-3 w-3" /> : <XCircle className="h-3 w-3" />}
-                    {testResult.msg}
+                  <span className={`text-xs flex items-center gap-1 ${testResult.success ? "text-green-600" : "text-red-500"}`}>
+                    {testResult.success ? <CheckCircle2 className="h-3 w-3" /> : <XCircle className="h-3 w-3" />}
+                    {testResult.success
+                      ? t("modelConfig.test.success", { ms: testResult.timings.total_ms })
+                      : t(`modelConfig.test.failure.${testResult.failure ?? "unknown"}`)}
+                  </span>
+                )}
+                {testError && (
+                  <span className="text-xs flex items-center gap-1 text-red-500">
+                    <XCircle className="h-3 w-3" />
+                    {testError}
                   </span>
                 )}
               </div>
+              {testResult && (
+                <div className="text-xs text-muted-foreground space-y-0.5">
+                  {!testResult.success && <p className="break-all">{testResult.message}</p>}
+                  <p>
+                    {[
+                      ["dns", testResult.timings.dns_ms],
+                      ["tcp", testResult.timings.tcp_connect_ms],
+                      ["tls", testResult.timings.tls_handshake_ms],
+                      ["firstByte", testResult.timings.first_byte_ms],
+                    ]
+                      .filter(([, v]) => v !== null)
+                      .map(([k, v]) => `${t(`modelConfig.test.timing.${k}`)} ${v} ms`)
+                      .join(" · ")}
+                  </p>
+                  {testResult.rate_limit && (
+                    <p>
+                      {testResult.rate_limit.requests_remaining !== null &&
+                        t("modelConfig.test.requestsRemaining", { count: testResult.rate_limit.requests_remaining })}
+                      {testResult.rate_limit.tokens_remaining !== null &&
+                        ` ${t("modelConfig.test.tokensRemaining", { count: testResult.rate_limit.tokens_remaining })}`}
+                      {testResult.rate_limit.retry_after_secs !== null &&
+                        ` ${t("modelConfig.test.retryAfter", { secs: testResult.rate_limit.retry_after_secs })}`}
+                    </p>
+                  )}
+                </div>
+              )}
             </CardContent>
           </Card>
         )}
@@ -1,29 +1,135 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { useNavigate } from "react-router-dom";
 import { useTranslation } from "react-i18next";
+import { invoke } from "@tauri-apps/api/core";
 import { ArrowLeft, ArrowRight, ExternalLink } from "lucide-react";
 import { WizardLayout } from "@/components/WizardLayout";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
-import { useWizard } from "@/context/WizardContext";
+import { useWizard, type PlatformEntry } from "@/context/WizardContext";
 
-const PLATFORMS = [
-  { id: "whatsapp", name: "WhatsApp", tokenLabel: "Phone Number ID + Token", url: "https://developers.facebook.com/docs/whatsapp/cloud-api/get-started" },
-  { id: "telegram", name: "Telegram", tokenLabel: "Bot Token", url: "https://core.telegram.org/bots#botfather" },
-  { id: "discord", name: "Discord", tokenLabel: "Bot Token", url: "https://discord.com/developers/applications" },
-  { id: "slack", name: "Slack", tokenLabel: "Bot Token", url: "https://api.slack.com/apps" },
-  { id: "wechat", name: "微信", tokenLabel: "AppID + AppSecret", url: "https://mp.weixin.qq.com/" },
-];
+type FieldKind = "text" | "secret" | "select" | "multi_select" | "list";
+
+interface PlatformField {
+  key: string;
+  label: string;
+  kind: FieldKind;
+  required: boolean;
+  required_when?: { field: string; equals: string };
+  options?: string[];
+  default?: unknown;
+  help?: string;
+}
+
+interface PlatformSchema {
+  platform: string;
+  name: string;
+  docs_url: string;
+  testable: boolean;
+  fields: PlatformField[];
+}
+
+const inputClass = "w-full px-3 py-2 rounded-md border border-input bg-background text-sm";
+
+function newEntry(schema: PlatformSchema): PlatformEntry {
+  const entry: PlatformEntry = { platform: schema.platform };
+  for (const field of schema.fields) {
+    if (field.default !== undefined) entry[field.key] = field.default;
+  }
+  return entry;
+}
+
+function isRequired(field: PlatformField, entry: PlatformEntry) {
+  if (field.required) return true;
+  const when = field.required_when;
+  return !!when && entry[when.field] === when.equals;
+}
+
+function FieldInput({
+  field,
+  value,
+  onChange,
+}: {
+  field: PlatformField;
+  value: unknown;
+  onChange: (value: unknown) => void;
+}) {
+  switch (field.kind) {
+    case "select":
+      return (
+        <select value={String(value ?? "")} onChange={(e) => onChange(e.target.value)} className={inputClass}>
+          {field.options?.map((o) => (
+            <option key={o} value={o}>
+              {o}
+            </option>
+          ))}
+        </select>
+      );
+    case "multi_select": {
+      const selected = Array.isArray(value) ? (value as string[]) : [];
+      return (
+        <div className="flex flex-wrap gap-3">
+          {field.options?.map((o) => (
+            <label key={o} className="flex items-center gap-1 text-sm">
+              <input
+                type="checkbox"
+                checked={selected.includes(o)}
+                onChange={(e) =>
+                  onChange(e.target.checked ? [...selected, o] : selected.filter((s) => s !== o))
+                }
+              />
+              {o}
+            </label>
+          ))}
+        </div>
+      );
+    }
+    case "list":
+      return (
+        <textarea
+          rows={2}
+          value={Array.isArray(value) ? value.join("\n") : ""}
+          onChange={(e) =>
+            onChange(
+              e.target.value
+                .split("\n")
+                .map((v) => v.trim())
+                .filter(Boolean)
+            )
+          }
+          className={inputClass}
+        />
+      );
+    default:
+      return (
+        <input
+          type={field.kind === "secret" ? "password" : "text"}
+          value={String(value ?? "")}
+          onChange={(e) => onChange(e.target.value)}
+          placeholder={field.label}
+          className={inputClass}
+        />
+      );
+  }
+}
 
 export default function PlatformConfig() {
   const { t } = useTranslation();
   const navigate = useNavigate();
   const { platforms, setPlatforms } = useWizard();
+  const [schemas, setSchemas] = useState<PlatformSchema[]>([]);
   const [selected, setSelected] = useState<Set<string>>(
     new Set(platforms.map((p) => p.platform))
   );
 
-  const togglePlatform = (id: string) => {
+  useEffect(() => {
+    invoke<PlatformSchema[]>("get_platform_schemas")
+      .then(setSchemas)
+      .catch(() => setSchemas([]));
+  }, []);
+
+  const togglePlatform = (schema: PlatformSchema) => {
+    const id = schema.platform;
     const next = new Set(selected);
     if (next.has(id)) {
       next.delete(id);
@@ -31,23 +137,20 @@ export default function PlatformConfig() {
     } else {
       next.add(id);
       if (!platforms.find((p) => p.platform === id)) {
-        setPlatforms([...platforms, { platform: id, token: "" }]);
+        setPlatforms([...platforms, newEntry(schema)]);
       }
     }
     setSelected(next);
   };
 
-  const updateToken = (platformId: string, token: string) => {
+  const updateField = (platformId: string, key: string, value: unknown) => {
     setPlatforms(
       platforms.map((p) =>
-        p.platform === platformId ? { ...p, token } : p
+        p.platform === platformId ? { ...p, [key]: value } : p
       )
     );
   };
 
-  const getToken = (platformId: string) =>
-    platforms.find((p) => p.platform === platformId)?.token ?? "";
-
   return (
     <WizardLayout step={5}>
       <div className="max-w-2xl mx-auto space-y-5">
@@ -58,19 +161,19 @@ export default function PlatformConfig() {
 
         {/* Platform cards */}
         <div className="grid grid-cols-2 gap-2">
-          {PLATFORMS.map((p) => (
+          {schemas.map((p) => (
             <button
-              key={p.id}
-              onClick={() => togglePlatform(p.id)}
+              key={p.platform}
+              onClick={() => togglePlatform(p)}
               className={`flex items-center gap-2 px-3 py-2 rounded-lg border text-sm transition-colors text-left ${
-                selected.has(p.id)
+                selected.has(p.platform)
                   ? "border-primary bg-primary/5 font-medium"
                   : "border-border hover:border-primary/50"
               }`}
             >
               <span className="truncate">{p.name}</span>
               <a
-                href={p.url}
+                href={p.docs_url}
                 target="_blank"
                 rel="noreferrer"
                 onClick={(e) => e.stopPropagation()}
@@ -82,29 +185,36 @@ export default function PlatformConfig() {
           ))}
         </div>
 
-        {/* Token inputs for selected platforms */}
+        {/* Settings forms for selected platforms */}
         {Array.from(selected).map((id) => {
-          const plat = PLATFORMS.find((p) => p.id === id);
-          if (!plat) return null;
+          const schema = schemas.find((s) => s.platform === id);
+          const entry = platforms.find((p) => p.platform === id);
+          if (!schema || !entry) return null;
           return (
             <Card key={id}>
               <CardHeader className="pb-2">
-                <CardTitle className="text-sm">{plat.name}</CardTitle>
+                <CardTitle className="text-sm">{schema.name}</CardTitle>
               </CardHeader>
-              <CardContent>
-                <label className="text-xs text-muted-foreground mb-1 block">{plat.tokenLabel}</label>
-                <input
-                  type="text"
-                  value={getToken(id)}
-                  onChange={(e) => updateToken(id, e.target.value)}
-                  placeholder={plat.tokenLabel}
-                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm"
-                />
+              <CardContent className="space-y-3">
+                {schema.fields.map((field) => (
+                  <div key={field.key}>
+                    <label className="text-xs text-muted-foreground mb-1 block">
+                      {field.label}
+                      {isRequired(field, entry) && " *"}
+                    </label>
+                    <FieldInput
+                      field={field}
+                      value={entry[field.key]}
+                      onChange={(value) => updateField(id, field.key, value)}
+                    />
+                    {field.help && <p className="text-xs text-muted-foreground mt-1">{field.help}</p>}
+                  </div>
+                ))}
                 <a
-                  href={plat.url}
+                  href={schema.docs_url}
                   target="_blank"
                   rel="noreferrer"
-                  className="text-xs text-muted-foreground hover:text-primary mt-1 inline-flex items-center gap-1"
+                  className="text-xs text-muted-foreground hover:text-primary inline-flex items-center gap-1"
                 >
                   {t("platformConfig.howToGet")}
                   <ExternalLink className="h-3 w-3" />
@@ -1,41 +1,217 @@
-import { useEffect, useState } from "react";
+import { useEffect, useRef, useState } from "react";
 import { useNavigate } from "react-router-dom";
 import { useTranslation } from "react-i18next";
 import { invoke } from "@tauri-apps/api/core";
-import { ArrowLeft, Save, CheckCircle2 } from "lucide-react";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { ArrowLeft, Save, CheckCircle2, RefreshCw, Plus, Trash2, Activity } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
 import { LanguageSwitch } from "@/components/LanguageSwitch";
+import type { PlatformEntry } from "@/context/WizardContext";
+
+type FallbackTrigger =
+  | { type: "rate_limited" }
+  | { type: "timeout"; after_ms: number }
+  | { type: "error" };
+
+interface ModelFallback {
+  provider: string;
+  model: string;
+  api_key?: string;
+  api_endpoint?: string;
+  triggers: FallbackTrigger[];
+}
 
 interface ConfigData {
   model_provider?: string;
   model_name?: string;
   api_key?: string;
   api_endpoint?: string;
+  model_fallbacks?: ModelFallback[];
   gateway_port: number;
-  platforms: { platform: string; token: string }[];
+  platforms: PlatformEntry[];
+}
+
+interface ConfigDiagnostic {
+  /** JSON pointer of the offending value, e.g. `/model_fallbacks/0/provider`. */
+  pointer: string;
+  severity: "error" | "warning";
+  message: string;
+}
+
+/** Pointers the form has an input for; other diagnostics are listed at the top. */
+const FIELD_POINTER =
+  /^\/(model_provider|model_name|api_key|api_endpoint|gateway_port|model_fallbacks(\/\d+(\/[a-z_]+(\/\d+\/after_ms)?)?)?)$/;
+
+interface ChainTestResult {
+  hops: { index: number; provider: string; model: string; result: { success: boolean; message: string } }[];
+  serving_hop: number | null;
+}
+
+const DEFAULT_TRIGGERS: FallbackTrigger[] = [
+  { type: "rate_limited" },
+  { type: "timeout", after_ms: 30000 },
+  { type: "error" },
+];
+
+interface ConfigChangedPayload {
+  revision: string | null;
+  changes: { path: string; kind: string }[];
+  error: string | null;
 }
 
 export default function SettingsPage() {
   const { t } = useTranslation();
   const navigate = useNavigate();
   const [config, setConfig] = useState<ConfigData | null>(null);
+  const [externalChange, setExternalChange] = useState(false);
   const [saved, setSaved] = useState(false);
+  const [saveError, setSaveError] = useState<string | null>(null);
+  const [diagnostics, setDiagnostics] = useState<ConfigDiagnostic[]>([]);
+  const [chainError, setChainError] = useState<string | null>(null);
+  const [chainTest, setChainTest] = useState<ChainTestResult | null>(null);
+  const [testingChain, setTestingChain] = useState(false);
+  // Read from the event listener, so kept in refs rather than state
+  const revision = useRef<string | null>(null);
+  const dirty = useRef(false);
+  const saving = useRef(false);
 
-  useEffect(() => {
-    invoke<ConfigData>("read_openclaw_config")
-      .then(setConfig)
+  const loadConfig = () => {
+    Promise.all([
+      invoke<ConfigData>("read_openclaw_config"),
+      invoke<string | null>("get_config_revision"),
+    ])
+      .then(([cfg, rev]) => {
+        setConfig(cfg);
+        revision.current = rev;
+      })
       .catch(() => setConfig({ gateway_port: 18789, platforms: [] }));
+    dirty.current = false;
+    setExternalChange(false);
+    setSaveError(null);
+  };
+
+  useEffect(loadConfig, []);
+
+  // Pick up edits made outside the installer; never overwrite unsaved input
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+    listen<ConfigChangedPayload>("config-changed", (event) => {
+      const { revision: rev, error } = event.payload;
+      // Our own save is reported too; its revision arrives with the response
+      if (saving.current || rev === revision.current) return;
+      if (dirty.current || error || !rev) {
+        setExternalChange(true);
+        return;
+      }
+      // The event carries no config; read it back like on first load
+      loadConfig();
+    }).then((fn) => {
+      unlisten = fn;
+    });
+    return () => {
+      unlisten?.();
+    };
   }, []);
 
+  // Re-validate as the form changes so problems show next to their field
+  useEffect(() => {
+    if (!config) return;
+    const timer = setTimeout(() => {
+      invoke<{ errors: ConfigDiagnostic[]; warnings: ConfigDiagnostic[] }>("validate_openclaw_config", { config })
+        .then((v) => setDiagnostics([...v.errors, ...v.warnings]))
+        .catch(() => setDiagnostics([]));
+    }, 400);
+    return () => clearTimeout(timer);
+  }, [config]);
+
+  // `nested` also matches values below the pointer, e.g. a trigger's timeout
+  const issuesAt = (pointer: string, nested = false) =>
+    diagnostics.filter((d) => d.pointer === pointer || (nested && d.pointer.startsWith(`${pointer}/`)));
+
+  const inputClass = (pointer: string, width = "w-full") => {
+    const issues = issuesAt(pointer);
+    const border = issues.some((d) => d.severity === "error")
+      ? "border-red-500"
+      : issues.length > 0
+        ? "border-amber-400"
+        : "border-input";
+    return `${width} px-3 py-2 rounded-md border ${border} bg-background text-sm`;
+  };
+
+  const fieldIssues = (pointer: string, nested = false) =>
+    issuesAt(pointer, nested).map((d, i) => (
+      <p key={i} className={`text-xs mt-1 ${d.severity === "error" ? "text-red-600" : "text-amber-700"}`}>
+        {d.message}
+      </p>
+    ));
+
+  const otherIssues = diagnostics.filter((d) => !FIELD_POINTER.test(d.pointer));
+
+  const update = (next: ConfigData) => {
+    setConfig(next);
+    dirty.current = true;
+  };
+
+  const fallbacks = config?.model_fallbacks ?? [];
+
+  const updateFallback = (index: number, next: ModelFallback | null) => {
+    if (!config) return;
+    const list = [...fallbacks];
+    if (next) list[index] = next;
+    else list.splice(index, 1);
+    update({ ...config, model_fallbacks: list });
+    setChainTest(null);
+  };
+
+  const toggleTrigger = (hop: ModelFallback, type: "rate_limited" | "error") => {
+    const has = hop.triggers.some((t) => t.type === type);
+    return {
+      ...hop,
+      triggers: has ? hop.triggers.filter((t) => t.type !== type) : [...hop.triggers, { type }],
+    };
+  };
+
+  const setTimeoutTrigger = (hop: ModelFallback, seconds: number) => {
+    const rest = hop.triggers.filter((t) => t.type !== "timeout");
+    return {
+      ...hop,
+      triggers: seconds > 0 ? [...rest, { type: "timeout" as const, after_ms: seconds * 1000 }] : rest,
+    };
+  };
+
+  const handleTestChain = async () => {
+    if (!config) return;
+    setTestingChain(true);
+    setChainError(null);
+    try {
+      setChainTest(await invoke<ChainTestResult>("test_model_chain", { config }));
+    } catch (err) {
+      setChainError(String(err));
+      setChainTest(null);
+    } finally {
+      setTestingChain(false);
+    }
+  };
+
   const handleSave = async () => {
     if (!config) return;
+    saving.current = true;
     try {
-      await invoke("write_openclaw_config", { config });
+      revision.current = await invoke<string>("write_openclaw_config", {
+        config,
+        expectedRevision: revision.current,
+      });
+      dirty.current = false;
+      setExternalChange(false);
+      setSaveError(null);
       setSaved(true);
       setTimeout(() => setSaved(false), 2000);
     } catch (err) {
       console.error("Save failed:", err);
+      setSaveError(String(err));
+    } finally {
+      saving.current = false;
     }
   };
 
@@ -49,6 +225,26 @@ export default function SettingsPage() {
       </header>
 
       <main className="flex-1 overflow-y-auto px-6 py-4 space-y-4">
+        {(externalChange || saveError) && (
+          <div className="flex items-center justify-between gap-3 rounded-md border border-amber-300 bg-amber-50 px-3 py-2 text-sm text-amber-900">
+            <span>{externalChange ? t("settings.externalChange") : saveError}</span>
+            <Button variant="outline" size="sm" onClick={loadConfig} className="gap-1">
+              <RefreshCw className="h-3 w-3" />
+              {t("settings.reload")}
+            </Button>
+          </div>
+        )}
+
+        {otherIssues.length > 0 && (
+          <div className="rounded-md border border-amber-300 bg-amber-50 px-3 py-2 text-xs space-y-1">
+            {otherIssues.map((d, i) => (
+              <p key={i} className={d.severity === "error" ? "text-red-700" : "text-amber-900"}>
+                {d.pointer}: {d.message}
+              </p>
+            ))}
+          </div>
+        )}
+
         {/* Model config */}
         <Card>
           <CardHeader className="pb-3">
@@ -60,36 +256,151 @@ export default function SettingsPage() {
               <input
                 type="text"
                 value={config.model_provider ?? ""}
-                onChange={(e) => setConfig({ ...config, model_provider: e.target.value })}
-                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm"
+                onChange={(e) => update({ ...config, model_provider: e.target.value })}
+                className={inputClass("/model_provider")}
               />
+              {fieldIssues("/model_provider")}
             </div>
             <div>
               <label className="text-xs text-muted-foreground mb-1 block">{t("settings.model")}</label>
               <input
                 type="text"
                 value={config.model_name ?? ""}
-                onChange={(e) => setConfig({ ...config, model_name: e.target.value })}
-                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm"
+                onChange={(e) => update({ ...config, model_name: e.target.value })}
+                className={inputClass("/model_name")}
               />
+              {fieldIssues("/model_name")}
             </div>
             <div>
               <label className="text-xs text-muted-foreground mb-1 block">{t("settings.apiKey")}</label>
               <input
                 type="password"
                 value={config.api_key ?? ""}
-                onChange={(e) => setConfig({ ...config, api_key: e.target.value })}
-                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm"
+                onChange={(e) => update({ ...config, api_key: e.target.value })}
+                className={inputClass("/api_key")}
               />
+              {fieldIssues("/api_key")}
             </div>
             <div>
               <label className="text-xs text-muted-foreground mb-1 block">{t("settings.apiEndpoint")}</label>
               <input
                 type="text"
                 value={config.api_endpoint ?? ""}
-                onChange={(e) => setConfig({ ...config, api_endpoint: e.target.value })}
-                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm"
+                onChange={(e) => update({ ...config, api_endpoint: e.target.value })}
+                className={inputClass("/api_endpoint")}
               />
+              {fieldIssues("/api_endpoint")}
+            </div>
+          </CardContent>
+        </Card>
+
+        {/* Fallback chain */}
+        <Card>
+          <CardHeader className="pb-3">
+            <CardTitle className="text-base">{t("settings.fallbackSection")}</CardTitle>
+          </CardHeader>
+          <CardContent className="space-y-3">
+            <p className="text-xs text-muted-foreground">{t("settings.fallbackHint")}</p>
+            {fallbacks.map((hop, i) => {
+              const timeout = hop.triggers.find((tr) => tr.type === "timeout");
+              const status = chainTest?.hops.find((h) => h.index === i + 1);
+              const at = (field: string) => `/model_fallbacks/${i}/${field}`;
+              return (
+                <div key={i} className="rounded-md border p-3 space-y-2">
+                  <div className="flex items-center gap-2">
+                    <span className="text-xs text-muted-foreground w-5">{i + 1}.</span>
+                    <input
+                      type="text"
+                      placeholder={t("settings.provider")}
+                      value={hop.provider}
+                      onChange={(e) => updateFallback(i, { ...hop, provider: e.target.value })}
+                      className={inputClass(at("provider"), "flex-1")}
+                    />
+                    <input
+                      type="text"
+                      placeholder={t("settings.model")}
+                      value={hop.model}
+                      onChange={(e) => updateFallback(i, { ...hop, model: e.target.value })}
+                      className={inputClass(at("model"), "flex-1")}
+                    />
+                    <Button variant="outline" size="sm" onClick={() => updateFallback(i, null)}>
+                      <Trash2 className="h-3 w-3" />
+                    </Button>
+                  </div>
+                  {fieldIssues(at("provider"))}
+                  {fieldIssues(at("model"))}
+                  <input
+                    type="password"
+                    placeholder={t("settings.fallbackKey")}
+                    value={hop.api_key ?? ""}
+                    onChange={(e) => updateFallback(i, { ...hop, api_key: e.target.value || undefined })}
+                    className={inputClass(at("api_key"))}
+                  />
+                  {fieldIssues(at("api_key"))}
+                  <div className="flex flex-wrap items-center gap-4 text-xs">
+                    <span className="text-muted-foreground">{t("settings.fallbackWhen")}</span>
+                    {(["rate_limited", "error"] as const).map((type) => (
+                      <label key={type} className="flex items-center gap-1">
+                        <input
+                          type="checkbox"
+                          checked={hop.triggers.some((tr) => tr.type === type)}
+                          onChange={() => updateFallback(i, toggleTrigger(hop, type))}
+                        />
+                        {t(`settings.trigger.${type}`)}
+                      </label>
+                    ))}
+                    <label className="flex items-center gap-1">
+                      {t("settings.trigger.timeout")}
+                      <input
+                        type="number"
+                        min={0}
+                        value={timeout && timeout.type === "timeout" ? timeout.after_ms / 1000 : 0}
+                        onChange={(e) => updateFallback(i, setTimeoutTrigger(hop, parseInt(e.target.value) || 0))}
+                        className="w-16 px-2 py-1 rounded-md border border-input bg-background"
+                      />
+                      s
+                    </label>
+                  </div>
+                  {fieldIssues(at("triggers"), true)}
+                  {fieldIssues(at("api_endpoint"))}
+                  {status && (
+                    <p className={`text-xs ${status.result.success ? "text-green-600" : "text-red-600"}`}>
+                      {status.result.success ? t("settings.hopHealthy") : status.result.message}
+                    </p>
+                  )}
+                </div>
+              );
+            })}
+            {chainTest && (
+              <p className="text-xs text-muted-foreground">
+                {chainTest.serving_hop === null
+                  ? t("settings.chainDown")
+                  : chainTest.serving_hop === 0
+                    ? t("settings.chainPrimary")
+                    : t("settings.chainServing", { hop: chainTest.serving_hop })}
+              </p>
+            )}
+            {fieldIssues("/model_fallbacks")}
+            {chainError && <p className="text-xs text-red-600">{chainError}</p>}
+            <div className="flex gap-2">
+              <Button
+                variant="outline"
+                size="sm"
+                className="gap-1"
+                onClick={() =>
+                  update({
+                    ...config,
+                    model_fallbacks: [...fallbacks, { provider: "", model: "", triggers: DEFAULT_TRIGGERS }],
+                  })
+                }
+              >
+                <Plus className="h-3 w-3" />
+                {t("settings.addFallback")}
+              </Button>
+              <Button variant="outline" size="sm" className="gap-1" onClick={handleTestChain} disabled={testingChain}>
+                <Activity className="h-3 w-3" />
+                {t("settings.testChain")}
+              </Button>
             </div>
           </CardContent>
         </Card>
@@ -105,9 +416,10 @@ export default function SettingsPage() {
               <input
                 type="number"
                 value={config.gateway_port}
-                onChange={(e) => setConfig({ ...config, gateway_port: parseInt(e.target.value) || 18789 })}
-                className="w-32 px-3 py-2 rounded-md border border-input bg-background text-sm"
+                onChange={(e) => update({ ...config, gateway_port: parseInt(e.target.value) || 18789 })}
+                className={inputClass("/gateway_port", "w-32")}
               />
+              {fieldIssues("/gateway_port")}
             </div>
           </CardContent>
         </Card>